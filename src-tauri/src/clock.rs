@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A reading of the clocks session tracking cares about. `awake` stops while
+/// the machine is suspended and `since_boot` does not, so the difference
+/// between two readings is time spent asleep. `wall` can jump when NTP or
+/// the user adjusts the clock, so it is only used to place events in time.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockReading {
+    awake: Duration,
+    since_boot: Duration,
+    wall: Duration,
+}
+
+/// What happened between two readings.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockDelta {
+    /// Part of `elapsed` the machine spent suspended.
+    pub suspended: Duration,
+    /// Seconds the wall clock was moved by, positive when set forward.
+    pub wall_jump_secs: i64,
+}
+
+impl ClockReading {
+    pub fn now() -> Self {
+        let (awake, since_boot) = read_clocks();
+        Self {
+            awake,
+            since_boot,
+            wall: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn since(&self, earlier: &ClockReading) -> ClockDelta {
+        let elapsed = self.since_boot.saturating_sub(earlier.since_boot);
+        let awake = self.awake.saturating_sub(earlier.awake);
+        let wall = self.wall.as_millis() as i64 - earlier.wall.as_millis() as i64;
+        ClockDelta {
+            suspended: elapsed.saturating_sub(awake),
+            wall_jump_secs: (wall - elapsed.as_millis() as i64) / 1000,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn clock_gettime(clock: libc::clockid_t) -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid timespec owned by this frame.
+    if unsafe { libc::clock_gettime(clock, &mut ts) } != 0 {
+        return None;
+    }
+    Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_clocks() -> (Duration, Duration) {
+    match (
+        clock_gettime(libc::CLOCK_MONOTONIC),
+        clock_gettime(libc::CLOCK_BOOTTIME),
+    ) {
+        (Some(awake), Some(since_boot)) => (awake, since_boot),
+        _ => fallback_clocks(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_clocks() -> (Duration, Duration) {
+    match (
+        clock_gettime(libc::CLOCK_UPTIME_RAW),
+        clock_gettime(libc::CLOCK_MONOTONIC_RAW),
+    ) {
+        (Some(awake), Some(since_boot)) => (awake, since_boot),
+        _ => fallback_clocks(),
+    }
+}
+
+#[cfg(windows)]
+fn read_clocks() -> (Duration, Duration) {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount64;
+    use windows_sys::Win32::System::WindowsProgramming::QueryUnbiasedInterruptTime;
+
+    let mut unbiased = 0u64;
+    // SAFETY: `unbiased` is a valid u64 owned by this frame.
+    if unsafe { QueryUnbiasedInterruptTime(&mut unbiased) } == 0 {
+        return fallback_clocks();
+    }
+    // SAFETY: GetTickCount64 has no preconditions.
+    let since_boot = Duration::from_millis(unsafe { GetTickCount64() });
+    (Duration::from_nanos(unbiased * 100), since_boot)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+)))]
+fn read_clocks() -> (Duration, Duration) {
+    fallback_clocks()
+}
+
+/// Without a suspend-aware clock both readings come from `Instant`, so
+/// suspend goes undetected but wall clock jumps are still ignored.
+fn fallback_clocks() -> (Duration, Duration) {
+    use std::sync::OnceLock;
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    let elapsed = ORIGIN.get_or_init(Instant::now).elapsed();
+    (elapsed, elapsed)
+}
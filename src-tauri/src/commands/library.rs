@@ -1,10 +1,22 @@
-use std::path::PathBuf;
-use tauri::State;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, State};
+use tokio::task;
 use uuid::Uuid;
 
 use crate::database::save_games;
-use crate::error::AppResult;
-use crate::models::GameMetadata;
+use crate::emulator;
+use crate::error::{AppError, AppResult};
+use crate::hooks;
+use crate::launcher;
+use crate::models::{
+    GameMetadata, HookCommands, LaunchOptions, LaunchProfile, LaunchTarget, ProcessPriority,
+    RunnerConfig, SandboxConfig, ScanCandidate,
+};
+use crate::process;
+use crate::runner;
+use crate::sandbox;
+use crate::scanner;
 use crate::state::AppState;
 
 #[tauri::command]
@@ -23,17 +35,41 @@ pub fn add_local_game(path: String, state: State<AppState>) -> AppResult<GameMet
         .unwrap_or("Unknown Game")
         .to_string();
 
-    let game = GameMetadata {
-        id: Uuid::new_v4().to_string(),
-        title,
-        path,
-        vndb_id: None,
-        cover_url: None,
-        play_time: 0,
-        is_finished: false,
-        last_played: None,
-        is_hidden: false,
-    };
+    let mut game = GameMetadata::new(title, path);
+    // Console images open in the emulator registered for their extension.
+    if let Some(target) = emulator::suggest_target(&path_buf, &state.settings.lock().emulators) {
+        game.target = target;
+    }
+
+    let mut games = state.games.lock();
+    games.push(game.clone());
+    save_games(&games)?;
+
+    Ok(game)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn add_uri_game(
+    title: String,
+    uri: String,
+    track_executable: String,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(AppError::Validation("Title cannot be empty".into()));
+    }
+    let uri = uri.trim().to_string();
+    launcher::validate_target(&LaunchTarget::Uri, &uri)?;
+    let track_executable = process::validate_executable_name(Some(&track_executable))?
+        .ok_or_else(|| {
+            AppError::Validation("URI launches need a process name to track".into())
+        })?;
+
+    let mut game = GameMetadata::new(title.to_string(), uri);
+    game.target = LaunchTarget::Uri;
+    game.track_executable = Some(track_executable);
 
     let mut games = state.games.lock();
     games.push(game.clone());
@@ -42,6 +78,82 @@ pub fn add_local_game(path: String, state: State<AppState>) -> AppResult<GameMet
     Ok(game)
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn scan_library(
+    roots: Vec<String>,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<ScanCandidate>> {
+    if roots.is_empty() {
+        return Err(AppError::Validation("No folders selected to scan".into()));
+    }
+
+    let roots: Vec<PathBuf> = roots.iter().map(PathBuf::from).collect();
+    if let Some(bad) = roots.iter().find(|r| !r.is_dir()) {
+        return Err(AppError::Validation(format!(
+            "Not a folder: {}",
+            bad.display()
+        )));
+    }
+
+    let known_paths: HashSet<String> = state
+        .games
+        .lock()
+        .iter()
+        .map(|g| scanner::normalize_path(Path::new(&g.path)))
+        .collect();
+
+    let candidates = task::spawn_blocking(move || {
+        scanner::scan_roots(&roots, &known_paths, |progress| {
+            let _ = app_handle.emit("library-scan-progress", progress);
+        })
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Library scan failed: {}", e)))?;
+
+    Ok(candidates)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn import_scanned_games(
+    candidates: Vec<ScanCandidate>,
+    state: State<AppState>,
+) -> AppResult<Vec<GameMetadata>> {
+    let mut games = state.games.lock();
+    let mut known_paths: HashSet<String> = games
+        .iter()
+        .map(|g| scanner::normalize_path(Path::new(&g.path)))
+        .collect();
+
+    let mut added = Vec::new();
+    for candidate in candidates {
+        if !known_paths.insert(scanner::normalize_path(Path::new(&candidate.path))) {
+            continue;
+        }
+        let title = if candidate.title.trim().is_empty() {
+            let folder_name = Path::new(&candidate.folder)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            scanner::title_from_folder(&folder_name)
+        } else {
+            candidate.title.trim().to_string()
+        };
+        let game = GameMetadata::new(title, candidate.path);
+        games.push(game.clone());
+        added.push(game);
+    }
+
+    if !added.is_empty() {
+        save_games(&games)?;
+    }
+    log::info!("import_scanned_games: Added {} games", added.len());
+
+    Ok(added)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn remove_game(id: String, state: State<AppState>) -> AppResult<()> {
@@ -53,9 +165,29 @@ pub fn remove_game(id: String, state: State<AppState>) -> AppResult<()> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn update_game(game: GameMetadata, state: State<AppState>) -> AppResult<()> {
+pub fn update_game(mut game: GameMetadata, state: State<AppState>) -> AppResult<()> {
+    game.launch_options = launcher::validate_launch_options(&game.launch_options)?;
+    game.profiles = game
+        .profiles
+        .iter()
+        .map(launcher::validate_profile)
+        .collect::<AppResult<_>>()?;
+    game.runner = game
+        .runner
+        .as_ref()
+        .map(runner::validate_runner)
+        .transpose()?;
+    game.track_executable =
+        process::validate_executable_name(game.track_executable.as_deref())?;
+    game.wrappers = launcher::validate_wrappers(&game.wrappers)?;
+    game.sandbox = sandbox::validate_sandbox(&game.sandbox)?;
+    game.target = launcher::validate_target(&game.target, &game.path)?;
+    launcher::check_target_options(&game.target, &game.launch_options)?;
+
     let mut games = state.games.lock();
     if let Some(existing) = games.iter_mut().find(|g| g.id == game.id) {
+        // Play time is derived from the session history.
+        game.play_time = existing.play_time;
         *existing = game;
     }
     save_games(&games)?;
@@ -72,3 +204,221 @@ pub fn set_game_hidden(id: String, hidden: bool, state: State<AppState>) -> AppR
     save_games(&games)?;
     Ok(())
 }
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_launch_options(
+    id: String,
+    options: LaunchOptions,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let options = launcher::validate_launch_options(&options)?;
+
+    modify_game(&id, &state, |game| {
+        launcher::check_target_options(&game.target, &options)?;
+        game.launch_options = options;
+        Ok(())
+    })
+}
+
+fn modify_game<F>(id: &str, state: &State<AppState>, f: F) -> AppResult<GameMetadata>
+where
+    F: FnOnce(&mut GameMetadata) -> AppResult<()>,
+{
+    let mut games = state.games.lock();
+    let game = games
+        .iter_mut()
+        .find(|g| g.id == id)
+        .ok_or_else(|| AppError::NotFound("Game not found".into()))?;
+    f(game)?;
+    let updated = game.clone();
+    save_games(&games)?;
+    Ok(updated)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn add_launch_profile(
+    game_id: String,
+    name: String,
+    path: String,
+    options: LaunchOptions,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let profile = launcher::validate_profile(&LaunchProfile {
+        id: Uuid::new_v4().to_string(),
+        name,
+        path,
+        launch_options: options,
+        target: LaunchTarget::default(),
+    })?;
+
+    modify_game(&game_id, &state, |game| {
+        game.profiles.push(profile);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_launch_profile(
+    game_id: String,
+    profile: LaunchProfile,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let profile = launcher::validate_profile(&profile)?;
+
+    modify_game(&game_id, &state, |game| {
+        let existing = game
+            .profiles
+            .iter_mut()
+            .find(|p| p.id == profile.id)
+            .ok_or_else(|| AppError::NotFound("Launch profile not found".into()))?;
+        *existing = profile;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn remove_launch_profile(
+    game_id: String,
+    profile_id: String,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    modify_game(&game_id, &state, |game| {
+        game.profiles.retain(|p| p.id != profile_id);
+        if game.default_profile_id.as_deref() == Some(profile_id.as_str()) {
+            game.default_profile_id = None;
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_default_profile(
+    game_id: String,
+    profile_id: Option<String>,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    modify_game(&game_id, &state, |game| {
+        if let Some(ref id) = profile_id {
+            if !game.profiles.iter().any(|p| &p.id == id) {
+                return Err(AppError::NotFound("Launch profile not found".into()));
+            }
+        }
+        game.default_profile_id = profile_id;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_game_runner(
+    game_id: String,
+    runner: Option<RunnerConfig>,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let runner = runner.as_ref().map(runner::validate_runner).transpose()?;
+
+    modify_game(&game_id, &state, |game| {
+        game.runner = runner;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_game_japanese_locale(
+    game_id: String,
+    enabled: bool,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    modify_game(&game_id, &state, |game| {
+        game.japanese_locale = enabled;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_game_hooks(
+    game_id: String,
+    hooks: HookCommands,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let hooks = hooks::normalize(hooks);
+    modify_game(&game_id, &state, |game| {
+        game.hooks = hooks;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_track_executable(
+    game_id: String,
+    name: Option<String>,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let name = process::validate_executable_name(name.as_deref())?;
+    modify_game(&game_id, &state, |game| {
+        game.track_executable = name;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_game_wrappers(
+    game_id: String,
+    wrappers: Vec<String>,
+    use_global_wrappers: bool,
+    priority: Option<ProcessPriority>,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let wrappers = launcher::validate_wrappers(&wrappers)?;
+    modify_game(&game_id, &state, |game| {
+        game.wrappers = wrappers;
+        game.use_global_wrappers = use_global_wrappers;
+        game.priority = priority;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_game_sandbox(
+    game_id: String,
+    sandbox: SandboxConfig,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let sandbox = sandbox::validate_sandbox(&sandbox)?;
+    modify_game(&game_id, &state, |game| {
+        game.sandbox = sandbox;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_launch_target(
+    game_id: String,
+    target: LaunchTarget,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    if let LaunchTarget::Rom {
+        emulator_id: Some(ref emulator_id),
+    } = target
+    {
+        let settings = state.settings.lock();
+        emulator::find(&settings.emulators, Some(emulator_id.trim()))?;
+    }
+    modify_game(&game_id, &state, |game| {
+        let target = launcher::validate_target(&target, &game.path)?;
+        launcher::check_target_options(&target, &game.launch_options)?;
+        game.target = target;
+        Ok(())
+    })
+}
@@ -1,9 +1,13 @@
 mod library;
+mod sessions;
 mod settings;
 mod system;
 mod vndb;
+mod wine;
 
 pub use library::*;
+pub use sessions::*;
 pub use settings::*;
 pub use system::*;
 pub use vndb::*;
+pub use wine::*;
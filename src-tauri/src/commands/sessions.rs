@@ -0,0 +1,104 @@
+use std::cmp::Reverse;
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::models::{PlaytimeAuditEntry, SessionRecord};
+use crate::playtime;
+use crate::state::AppState;
+
+/// Sessions overlapping `from..to` (unix seconds), newest first. Either bound
+/// and the game filter may be omitted.
+#[tauri::command]
+#[specta::specta]
+pub fn list_sessions(
+    game_id: Option<String>,
+    from: Option<u64>,
+    to: Option<u64>,
+    state: State<AppState>,
+) -> Vec<SessionRecord> {
+    let mut sessions: Vec<_> = state
+        .session_history
+        .lock()
+        .iter()
+        .filter(|r| game_id.as_ref().map_or(true, |id| &r.game_id == id))
+        .filter(|r| from.map_or(true, |from| r.ended_at >= from))
+        .filter(|r| to.map_or(true, |to| r.started_at <= to))
+        .cloned()
+        .collect();
+    sessions.sort_by_key(|r| Reverse(r.started_at));
+    sessions
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn add_manual_session(
+    game_id: String,
+    started_at: u64,
+    active_secs: u64,
+    reason: String,
+    state: State<AppState>,
+) -> AppResult<PlaytimeAuditEntry> {
+    playtime::add_session(&state, game_id, started_at, active_secs, &reason)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn edit_session(
+    session_id: String,
+    started_at: u64,
+    active_secs: u64,
+    reason: String,
+    state: State<AppState>,
+) -> AppResult<PlaytimeAuditEntry> {
+    playtime::edit_session(&state, &session_id, started_at, active_secs, &reason)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_session(
+    session_id: String,
+    reason: String,
+    state: State<AppState>,
+) -> AppResult<PlaytimeAuditEntry> {
+    playtime::delete_session(&state, &session_id, &reason)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn adjust_daily_playtime(
+    game_id: String,
+    date: String,
+    minutes: u64,
+    reason: String,
+    state: State<AppState>,
+) -> AppResult<PlaytimeAuditEntry> {
+    playtime::adjust_daily(&state, game_id, date, minutes, &reason)
+}
+
+/// Play time changes, newest first.
+#[tauri::command]
+#[specta::specta]
+pub fn list_playtime_audit(
+    game_id: Option<String>,
+    state: State<AppState>,
+) -> Vec<PlaytimeAuditEntry> {
+    let mut audit: Vec<_> = state
+        .playtime_audit
+        .lock()
+        .iter()
+        .filter(|e| game_id.as_ref().map_or(true, |id| &e.game_id == id))
+        .cloned()
+        .collect();
+    audit.sort_by_key(|e| Reverse(e.at));
+    audit
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn revert_playtime_change(
+    audit_id: String,
+    reason: Option<String>,
+    state: State<AppState>,
+) -> AppResult<PlaytimeAuditEntry> {
+    playtime::revert(&state, &audit_id, reason.as_deref())
+}
@@ -1,10 +1,23 @@
 use tauri::State;
+use uuid::Uuid;
 
 use crate::database::save_settings;
-use crate::error::AppResult;
-use crate::models::AppSettings;
+use crate::emulator;
+use crate::error::{AppError, AppResult};
+use crate::hooks;
+use crate::launcher;
+use crate::models::{
+    AppSettings, EmulatorConfig, HookCommands, IdleSourceKind, ProcessPriority, RunnerConfig,
+};
+use crate::runner;
 use crate::state::AppState;
 
+/// Longest resource sampling interval; slower samples say little about a
+/// session.
+const MAX_RESOURCE_SAMPLE_SECS: u64 = 3600;
+/// Longest idle threshold; past this a session would hardly ever be idle.
+const MAX_IDLE_THRESHOLD_MINS: u64 = 720;
+
 #[tauri::command]
 #[specta::specta]
 pub fn init_app() {}
@@ -80,3 +93,156 @@ pub fn set_discord_rpc_buttons(
     save_settings(&settings)?;
     Ok(())
 }
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_default_runner(runner: RunnerConfig, state: State<AppState>) -> AppResult<()> {
+    let runner = runner::validate_runner(&runner)?;
+
+    let mut settings = state.settings.lock();
+    settings.default_runner = runner;
+    save_settings(&settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_locale_emulator_command(
+    command: Option<String>,
+    state: State<AppState>,
+) -> AppResult<()> {
+    let command = command
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    if let Some(ref command) = command {
+        launcher::validate_locale_emulator_command(command)?;
+    }
+
+    let mut settings = state.settings.lock();
+    settings.locale_emulator_command = command;
+    save_settings(&settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_global_hooks(
+    hooks: HookCommands,
+    timeout_secs: u64,
+    state: State<AppState>,
+) -> AppResult<()> {
+    if timeout_secs == 0 {
+        return Err(AppError::Validation(
+            "Hook timeout must be at least one second".into(),
+        ));
+    }
+
+    let mut settings = state.settings.lock();
+    settings.hooks = hooks::normalize(hooks);
+    settings.hook_timeout_secs = timeout_secs;
+    save_settings(&settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_global_wrappers(
+    wrappers: Vec<String>,
+    priority: ProcessPriority,
+    state: State<AppState>,
+) -> AppResult<()> {
+    let wrappers = launcher::validate_wrappers(&wrappers)?;
+    let mut settings = state.settings.lock();
+    settings.wrappers = wrappers;
+    settings.priority = priority;
+    save_settings(&settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_emulators(
+    emulators: Vec<EmulatorConfig>,
+    state: State<AppState>,
+) -> AppResult<Vec<EmulatorConfig>> {
+    let mut validated: Vec<EmulatorConfig> = Vec::with_capacity(emulators.len());
+    for emulator in &emulators {
+        let mut emulator = emulator::validate_emulator(emulator)?;
+        if emulator.id.trim().is_empty() {
+            emulator.id = Uuid::new_v4().to_string();
+        }
+        if validated.iter().any(|e| e.id == emulator.id) {
+            return Err(AppError::Validation(format!(
+                "Duplicate emulator id: {}",
+                emulator.id
+            )));
+        }
+        validated.push(emulator);
+    }
+
+    let mut settings = state.settings.lock();
+    settings.emulators = validated.clone();
+    save_settings(&settings)?;
+    Ok(validated)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_auto_detect_games(enabled: bool, state: State<AppState>) -> AppResult<()> {
+    let mut settings = state.settings.lock();
+    settings.auto_detect_games = enabled;
+    save_settings(&settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_idle_detection(
+    source: IdleSourceKind,
+    threshold_mins: u64,
+    state: State<AppState>,
+) -> AppResult<()> {
+    // Idle detection is turned off through the source, so a threshold of 0
+    // would count every session as idle from the first tick.
+    if !(1..=MAX_IDLE_THRESHOLD_MINS).contains(&threshold_mins) {
+        return Err(AppError::Validation(format!(
+            "Idle threshold must be between 1 and {} minutes, got {}",
+            MAX_IDLE_THRESHOLD_MINS, threshold_mins
+        )));
+    }
+    let mut settings = state.settings.lock();
+    settings.idle_source = source;
+    settings.idle_threshold_mins = threshold_mins;
+    save_settings(&settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_resource_sampling(interval_secs: u64, state: State<AppState>) -> AppResult<()> {
+    if interval_secs > MAX_RESOURCE_SAMPLE_SECS {
+        return Err(AppError::Validation(format!(
+            "Resource sampling interval must be between 0 (off) and {} seconds, got {}",
+            MAX_RESOURCE_SAMPLE_SECS, interval_secs
+        )));
+    }
+    let mut settings = state.settings.lock();
+    settings.resource_sample_secs = interval_secs;
+    save_settings(&settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_day_start_hour(hour: u8, state: State<AppState>) -> AppResult<()> {
+    if hour > 23 {
+        return Err(AppError::Validation(format!(
+            "Day start hour must be between 0 and 23, got {}",
+            hour
+        )));
+    }
+    let mut settings = state.settings.lock();
+    settings.day_start_hour = hour;
+    save_settings(&settings)?;
+    Ok(())
+}
@@ -1,212 +1,528 @@
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::Instant;
-use tauri::{Emitter, Manager, State};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{Manager, State};
 use tokio::task;
 
-use crate::database::{get_current_timestamp, record_daily_playtime, save_games};
 use crate::discord;
+use crate::emulator;
 use crate::error::{AppError, AppResult};
-use crate::models::{GameExitedPayload, RunningGame};
-use crate::state::AppState;
+use crate::game_log;
+use crate::hooks::{self, HookContext};
+use crate::launcher::{self, LaunchPlan};
+use crate::models::{
+    GameMetadata, LaunchPreview, LaunchProfile, LaunchRequest, LaunchTarget, ResourceStats,
+    ResourceUsage, RunningSession, TrackedProcess,
+};
+use crate::process;
+use crate::runner::{self, WineContext};
+use crate::sandbox;
+use crate::session;
+use crate::state::{AppState, LaunchRequests};
+use crate::wine;
+
+const DETACHED_APPEAR_TIMEOUT: Duration = Duration::from_secs(30);
+const FORCE_STOP_GRACE: Duration = Duration::from_secs(10);
+/// Store clients may update or log in before starting the game.
+const URI_APPEAR_TIMEOUT: Duration = Duration::from_secs(120);
 
 #[tauri::command]
 #[specta::specta]
-pub fn launch_game(
+pub async fn launch_game(
     id: String,
     app_handle: tauri::AppHandle,
-    state: State<AppState>,
+    state: State<'_, AppState>,
 ) -> AppResult<()> {
-    let games = state.games.lock();
-    let game = games
+    start_game(id, None, app_handle, &state).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn launch_game_profile(
+    id: String,
+    profile_id: String,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    start_game(id, Some(profile_id), app_handle, &state).await
+}
+
+fn target_path(game: &GameMetadata, profile: Option<&LaunchProfile>) -> String {
+    profile
+        .map(|p| p.path.clone())
+        .unwrap_or_else(|| game.path.clone())
+}
+
+async fn start_game(
+    id: String,
+    profile_id: Option<String>,
+    app_handle: tauri::AppHandle,
+    state: &AppState,
+) -> AppResult<()> {
+    let game = state
+        .games
+        .lock()
         .iter()
         .find(|g| g.id == id)
+        .cloned()
         .ok_or_else(|| AppError::NotFound("Game not found".into()))?;
+    let (global_hooks, hook_timeout) = {
+        let settings = state.settings.lock();
+        (
+            settings.hooks.clone(),
+            Duration::from_secs(settings.hook_timeout_secs.max(1)),
+        )
+    };
 
-    let path = PathBuf::from(&game.path);
+    let profile = launcher::resolve_profile(&game, profile_id.as_deref())?;
+    let _slot = session::reserve(state, &game)?;
+    let ctx = HookContext {
+        game_id: game.id.clone(),
+        title: game.title.clone(),
+        path: target_path(&game, profile),
+        session_minutes: None,
+    };
+    let game_hooks = game.hooks.clone();
+    let pre_hooks = (global_hooks.clone(), game_hooks.clone(), ctx.clone());
+    task::spawn_blocking(move || {
+        let (global_hooks, game_hooks, ctx) = pre_hooks;
+        hooks::run_pre_launch(&global_hooks, &game_hooks, &ctx, hook_timeout)
+    })
+    .await
+    .map_err(|e| AppError::ProcessLaunch(format!("Pre-launch hook failed: {}", e)))??;
 
-    if !path.exists() {
-        return Err(AppError::ProcessLaunch(format!(
-            "Game executable not found: {}",
-            path.display()
-        )));
-    }
-    if !path.is_file() {
-        return Err(AppError::ProcessLaunch(format!(
-            "Path is not a file: {}",
-            path.display()
-        )));
+    let result = spawn_game(&game, profile_id, app_handle, state);
+    if result.is_err() {
+        // Undo whatever the pre-launch hook set up, e.g. a mounted image.
+        let _ = task::spawn_blocking(move || {
+            hooks::run_post_exit(&global_hooks, &game_hooks, &ctx, hook_timeout)
+        })
+        .await;
     }
+    result
+}
 
-    let mut child = Command::new(&path)
-        .current_dir(path.parent().unwrap_or(&path))
-        .spawn()
-        .map_err(|e| {
-            use std::io::ErrorKind;
-            match e.kind() {
-                ErrorKind::NotFound => AppError::ProcessLaunch(format!(
-                    "Executable not found or invalid: {}",
-                    path.display()
-                )),
-                ErrorKind::PermissionDenied => AppError::ProcessLaunch(format!(
-                    "Permission denied: cannot execute {}",
-                    path.display()
-                )),
-                _ => AppError::ProcessLaunch(format!(
-                    "Failed to launch game: {} ({})",
-                    e,
-                    path.display()
-                )),
-            }
-        })?;
-
-    let game_title = game.title.clone();
-    let cover_url = game.cover_url.clone();
-    let discord_start = discord::get_unix_timestamp();
+/// Whether no other library game runs in the Wine prefix at `prefix`.
+fn prefix_is_dedicated(game: &GameMetadata, prefix: &str, state: &AppState) -> bool {
+    let default_prefix = state.settings.lock().default_runner.prefix.clone();
+    let prefixes = state.wine_prefixes.lock();
+    let prefix_of = |other: &GameMetadata| match other.wine_prefix_id.as_deref() {
+        Some(id) => prefixes.iter().find(|p| p.id == id).map(|p| p.path.clone()),
+        None => other
+            .runner
+            .as_ref()
+            .map_or(default_prefix.clone(), |r| r.prefix.clone()),
+    };
+    let prefix = Path::new(prefix);
+    !state
+        .games
+        .lock()
+        .iter()
+        .filter(|other| other.id != game.id)
+        .any(|other| prefix_of(other).is_some_and(|p| Path::new(&p) == prefix))
+}
 
-    let start_time = Instant::now();
-    {
-        let mut running = state.running_game.lock();
-        *running = Some(RunningGame {
-            id: id.clone(),
-            start_time,
-            title: game_title.clone(),
-            cover_url: cover_url.clone(),
-            discord_start_timestamp: discord_start,
-        });
-    }
+/// A launch resolved down to the command that gets spawned.
+struct ResolvedLaunch<'a> {
+    profile: Option<&'a LaunchProfile>,
+    plan: LaunchPlan,
+    wine: Option<WineContext>,
+    detached_exe: Option<PathBuf>,
+}
 
-    {
+/// Applies the game's runner, prefix, locale and wrappers to its launch
+/// options.
+fn resolve_launch<'a>(
+    game: &'a GameMetadata,
+    profile_id: Option<&str>,
+    state: &AppState,
+) -> AppResult<ResolvedLaunch<'a>> {
+    let profile = launcher::resolve_profile(game, profile_id)?;
+    let mut plan = launcher::plan_launch(game, profile)?;
+    let (mut runner, locale_emulator, global_wrappers, global_priority, emulators) = {
         let settings = state.settings.lock();
-        if settings.discord_rpc_enabled {
-            // Get developer name from VN cache if available
-            let developer = game.vndb_id.as_ref().and_then(|vndb_id| {
-                state.vn_mem_cache.lock().get(vndb_id).and_then(|vn| {
-                    vn.developers.as_ref().and_then(|devs| {
-                        devs.first().map(|d| d.name.clone())
-                    })
-                })
-            });
-
-            let vndb_game_url = game.vndb_id.as_ref().map(|id| format!("https://vndb.org/{}", id));
-            let vndb_profile_url = settings.vndb_user_id.as_ref().map(|id| format!("https://vndb.org/{}", id));
-            const GITHUB_URL: &str = "https://github.com/betadyne/AlkaLauncher";
-
-            let mut buttons: Vec<(&str, String)> = Vec::new();
-
-            if settings.discord_btn_vndb_game {
-                if let Some(ref url) = vndb_game_url {
-                    buttons.push(("View on VNDB", url.clone()));
-                }
-            }
-            if settings.discord_btn_vndb_profile && buttons.len() < 2 {
-                if let Some(ref url) = vndb_profile_url {
-                    buttons.push(("My VNDB Profile", url.clone()));
-                }
+        (
+            game.runner
+                .clone()
+                .unwrap_or_else(|| settings.default_runner.clone()),
+            settings.locale_emulator_command.clone(),
+            settings.wrappers.clone(),
+            settings.priority,
+            settings.emulators.clone(),
+        )
+    };
+    let target = launcher::target_of(game, profile);
+    // Runners only apply to executables; everything else runs natively.
+    let (mut plan, wine) = match target {
+        LaunchTarget::Executable => {
+            if let Some(prefix_id) = game.wine_prefix_id.as_deref() {
+                let prefixes = state.wine_prefixes.lock();
+                let prefix = prefixes
+                    .iter()
+                    .find(|p| p.id == prefix_id)
+                    .ok_or_else(|| AppError::NotFound("Wine prefix not found".into()))?;
+                runner = wine::apply_prefix(runner, &mut plan, prefix)?;
             }
-            if settings.discord_btn_github && buttons.len() < 2 {
-                buttons.push(("GitHub", GITHUB_URL.to_string()));
+            let (plan, mut wine) = runner::apply_runner(plan, &runner)?;
+            if let (Some(wine), Some(prefix)) = (wine.as_mut(), runner.prefix.as_deref()) {
+                wine.dedicated = prefix_is_dedicated(game, prefix, state);
             }
+            (plan, wine)
+        }
+        LaunchTarget::Rom { emulator_id } => {
+            let emulator = emulator::find(&emulators, emulator_id.as_deref())?;
+            (emulator::apply_emulator(plan, emulator)?, None)
+        }
+        _ => (plan, None),
+    };
+    let mut detached_exe = None;
+    let runs_locally = matches!(target, LaunchTarget::Executable | LaunchTarget::Script { .. });
+    if game.japanese_locale && runs_locally {
+        (plan, detached_exe) = launcher::apply_japanese_locale(plan, locale_emulator.as_deref())?;
+    }
 
-            let button_refs: Vec<(&str, &str)> = buttons
-                .iter()
-                .map(|(label, url)| (*label, url.as_str()))
-                .collect();
+    if game.sandbox.enabled {
+        let target = PathBuf::from(target_path(game, profile));
+        plan = sandbox::apply_sandbox(plan, &game.sandbox, &target, wine.as_ref())?;
+    }
+
+    let mut wrappers = Vec::new();
+    if game.use_global_wrappers {
+        wrappers.extend(global_wrappers);
+    }
+    wrappers.extend(game.wrappers.iter().cloned());
+    let plan = launcher::apply_wrappers(
+        plan,
+        &wrappers,
+        game.priority.unwrap_or(global_priority),
+    )?;
 
-            log::info!("Discord RPC buttons: {:?}", button_refs.iter().map(|(l, _)| *l).collect::<Vec<_>>());
+    Ok(ResolvedLaunch {
+        profile,
+        plan,
+        wine,
+        detached_exe,
+    })
+}
 
-            let _ = state.discord_rpc.set_activity(
-                &game_title,
-                cover_url.as_deref(),
-                developer.as_deref(),
-                button_refs,
-                discord_start,
-            );
+fn spawn_game(
+    game: &GameMetadata,
+    profile_id: Option<String>,
+    app_handle: tauri::AppHandle,
+    state: &AppState,
+) -> AppResult<()> {
+    let id = game.id.clone();
+    let ResolvedLaunch {
+        profile,
+        mut plan,
+        wine,
+        detached_exe,
+    } = resolve_launch(game, profile_id.as_deref(), state)?;
+    let profile_id = profile.map(|p| p.id.clone());
+    // The OS opener hands a URI to a long-running client (e.g. Steam), so
+    // the session follows the watched process instead of what we spawn.
+    let uri_watch = match launcher::target_of(game, profile) {
+        LaunchTarget::Uri => Some(game.track_executable.clone().ok_or_else(|| {
+            AppError::Validation("URI launches need a process name to track".into())
+        })?),
+        _ => None,
+    };
+    log::info!(
+        "Launching {} ({}): {} (cwd {:?}, env {:?})",
+        game.title,
+        profile.map(|p| p.name.as_str()).unwrap_or("main"),
+        plan.command_line(),
+        plan.cwd,
+        plan.env
+    );
+    let session_tag = uuid::Uuid::new_v4().to_string();
+    if uri_watch.is_none() {
+        plan.env.push((process::SESSION_ENV.into(), session_tag.clone()));
+    }
+    if let Some(ref wine) = wine {
+        wine.prepare()?;
+    }
+    sandbox::prepare(&game.sandbox, wine.as_ref())?;
+    let output = match game_log::create(&id, &plan.command_line()) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            log::warn!("Failed to create log for {}: {}", game.title, e);
+            None
+        }
+    };
+    let logging = output.is_some();
+    let mut child = match plan.spawn(output) {
+        Ok(child) => child,
+        Err(e) => {
+            if logging {
+                game_log::discard(&id);
+            }
+            return Err(e);
+        }
+    };
+    let mut owns_log = false;
+    if logging {
+        match game_log::keep(&id) {
+            Ok(()) => owns_log = true,
+            Err(e) => log::warn!("Failed to rotate logs for {}: {}", game.title, e),
         }
     }
 
-    drop(games);
+    let hook_path = target_path(game, profile);
+    let tracked = TrackedProcess {
+        pid: child.id(),
+        pid_started_at: process::start_time(child.id()).unwrap_or_default(),
+        session_tag: uri_watch.is_none().then(|| session_tag.clone()),
+        hook_path: hook_path.clone(),
+    };
+    let start_time = session::begin(
+        state,
+        game,
+        profile_id.clone(),
+        tracked,
+        discord::get_unix_timestamp(),
+        Vec::new(),
+        ResourceStats::default(),
+    );
+    if let Some(running) = state.running_games.lock().get_mut(&id) {
+        running.owns_log = owns_log;
+    }
 
-    let app_handle_clone = app_handle.clone();
-    let game_id = id.clone();
+    let track_executable = game.track_executable.clone();
 
     tauri::async_runtime::spawn(async move {
-        let exit_result = task::spawn_blocking(move || child.wait()).await;
-
-        let minutes = start_time.elapsed().as_secs() / 60;
-        let state = app_handle_clone.state::<AppState>();
-
-        let _ = state.discord_rpc.clear_activity();
+        let handle = app_handle.clone();
+        let game_id = id.clone();
+        let waited = task::spawn_blocking(move || {
+            if let Some(name) = uri_watch {
+                let _ = child.wait();
+                let state = handle.state::<AppState>();
+                let Some(pid) = process::wait_for_executable_pid(&name, URI_APPEAR_TIMEOUT) else {
+                    session::discard(&state, &game_id, start_time);
+                    return (None, discord::get_unix_timestamp());
+                };
+                session::retarget(&state, &game_id, start_time, pid);
+                process::wait_for_process_tree(pid, None);
+                return (None, discord::get_unix_timestamp());
+            }
 
-        {
-            let mut games = state.games.lock();
-            if let Some(g) = games.iter_mut().find(|g| g.id == game_id) {
-                g.play_time += minutes;
-                g.last_played = Some(get_current_timestamp());
-                let _ = save_games(&games);
+            let waited =
+                process::wait_for_tree(&mut child, &session_tag, track_executable.as_deref());
+            // The wine loader can exit before the game it started does.
+            if let Some(wine) = wine.filter(|w| w.dedicated) {
+                wine.wait_for_prefix();
             }
-        }
+            // The session ends when the game's last process does, not when
+            // the wait for a detached one that never showed up times out.
+            let mut ended_at = discord::get_unix_timestamp();
+            // Locale Emulator starts the game detached and exits immediately.
+            if let Some(exe) = detached_exe {
+                if process::wait_for_detached_executable(&exe, DETACHED_APPEAR_TIMEOUT) {
+                    ended_at = discord::get_unix_timestamp();
+                }
+            }
+            let tracked_in_tree = waited.as_ref().is_ok_and(|(_, seen)| *seen);
+            if let Some(name) = track_executable.filter(|_| !tracked_in_tree) {
+                if process::wait_for_executable_name(&name, DETACHED_APPEAR_TIMEOUT) {
+                    ended_at = discord::get_unix_timestamp();
+                }
+            }
+            match waited {
+                Ok((status, _)) => (Some(status), ended_at),
+                Err(e) => {
+                    log::error!("Game process error: {}", e);
+                    (None, ended_at)
+                }
+            }
+        })
+        .await;
+        let (status, ended_at) = waited.unwrap_or_else(|_| (None, discord::get_unix_timestamp()));
 
-        record_daily_playtime(&game_id, minutes);
+        session::end(
+            &app_handle,
+            id,
+            profile_id,
+            start_time,
+            hook_path,
+            status,
+            ended_at,
+        )
+        .await;
+    });
 
-        {
-            let mut running = state.running_game.lock();
-            *running = None;
-        }
+    Ok(())
+}
 
-        let _ = app_handle.emit(
-            "game-exited",
-            GameExitedPayload {
-                game_id: game_id.clone(),
-                play_minutes: minutes,
-            },
-        );
+#[tauri::command]
+#[specta::specta]
+pub fn attach_to_process(
+    game_id: String,
+    pid: u32,
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+) -> AppResult<()> {
+    let game = state
+        .games
+        .lock()
+        .iter()
+        .find(|g| g.id == game_id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound("Game not found".into()))?;
 
-        if let Ok(Err(e)) = exit_result {
-            eprintln!("Game process error: {}", e);
-        }
-    });
+    let _slot = session::reserve(&state, &game)?;
 
+    if !session::track_external(&app_handle, &game, None, pid) {
+        return Err(AppError::NotFound(format!("No running process with pid {}", pid)));
+    }
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn stop_tracking(state: State<AppState>) -> AppResult<u64> {
-    let mut running = state.running_game.lock();
-    if let Some(game) = running.take() {
-        let elapsed = game.start_time.elapsed();
-        let minutes = elapsed.as_secs() / 60;
-        let game_id = game.id.clone();
-
-        let mut games = state.games.lock();
-        if let Some(g) = games.iter_mut().find(|g| g.id == game_id) {
-            g.play_time += minutes;
-            g.last_played = Some(get_current_timestamp());
-            save_games(&games)?;
-        }
-        drop(games);
+pub fn stop_tracking(game_id: String, state: State<AppState>) -> AppResult<u64> {
+    let Some(game) = state.running_games.lock().remove(&game_id) else {
+        return Ok(0);
+    };
+    session::checkpoint(&state);
+    session::refresh_discord_activity(&state);
 
-        record_daily_playtime(&game_id, minutes);
+    let record = game.to_record(discord::get_unix_timestamp());
+    session::record_session(&state, record)
+}
 
-        return Ok(minutes);
+/// Terminates a running game: its processes are asked to exit and killed if
+/// they are still around after a grace period. The session then ends as
+/// usual, with `force_stopped` set on its `game-exited` event.
+#[tauri::command]
+#[specta::specta]
+pub async fn force_stop_game(game_id: String, state: State<'_, AppState>) -> AppResult<()> {
+    let process = {
+        let mut running = state.running_games.lock();
+        let game = running
+            .get_mut(&game_id)
+            .ok_or_else(|| AppError::Validation("Game is not running".into()))?;
+        game.force_stopped = true;
+        game.process.clone()
+    };
+    let track_executable = state
+        .games
+        .lock()
+        .iter()
+        .find(|g| g.id == game_id)
+        .and_then(|g| g.track_executable.clone());
+
+    let killed = task::spawn_blocking(move || {
+        process::terminate_session(&process, track_executable.as_deref(), FORCE_STOP_GRACE)
+    })
+    .await
+    .map_err(|e| AppError::ProcessLaunch(format!("Failed to stop game: {}", e)))?;
+    if killed {
+        log::info!("{} did not exit in time and was killed", game_id);
     }
-    Ok(0)
+    Ok(())
+}
+
+/// The end of the output captured from the game's last run, if any.
+#[tauri::command]
+#[specta::specta]
+pub fn get_game_log(game_id: String) -> AppResult<Option<String>> {
+    game_log::read_latest(&game_id)
+}
+
+/// The command a launch would run, with every wrapper applied, without
+/// starting anything.
+#[tauri::command]
+#[specta::specta]
+pub fn preview_launch(
+    game_id: String,
+    profile_id: Option<String>,
+    state: State<AppState>,
+) -> AppResult<LaunchPreview> {
+    let game = state
+        .games
+        .lock()
+        .iter()
+        .find(|g| g.id == game_id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound("Game not found".into()))?;
+    let plan = resolve_launch(&game, profile_id.as_deref(), &state)?.plan;
+    Ok(LaunchPreview {
+        command_line: plan.command_line(),
+        cwd: plan.cwd.to_string_lossy().to_string(),
+        env: plan
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect(),
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn poll_running_game(state: State<AppState>) -> Vec<RunningSession> {
+    let now = discord::get_unix_timestamp();
+    let running = state.running_games.lock();
+    let mut sessions: Vec<RunningSession> = running
+        .values()
+        .map(|r| RunningSession {
+            game_id: r.id.clone(),
+            profile_id: r.profile_id.clone(),
+            title: r.title.clone(),
+            cover_url: r.cover_url.clone(),
+            started_at: r.started_at,
+            elapsed_secs: r.active_secs(now),
+        })
+        .collect();
+    sessions.sort_by_key(|s| s.started_at);
+    sessions
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn poll_running_game(state: State<AppState>) -> Option<String> {
-    let running = state.running_game.lock();
-    running.as_ref().map(|g| g.id.clone())
+pub fn get_elapsed_time(state: State<AppState>) -> HashMap<String, u64> {
+    let now = discord::get_unix_timestamp();
+    let running = state.running_games.lock();
+    running
+        .values()
+        .map(|r| (r.id.clone(), r.active_secs(now)))
+        .collect()
 }
 
+/// Latest CPU and memory sample of each running game that has one.
 #[tauri::command]
 #[specta::specta]
-pub fn get_elapsed_time(state: State<AppState>) -> u64 {
-    let running = state.running_game.lock();
+pub fn get_resource_usage(state: State<AppState>) -> HashMap<String, ResourceUsage> {
+    let running = state.running_games.lock();
     running
-        .as_ref()
-        .map(|r| r.start_time.elapsed().as_secs())
-        .unwrap_or(0)
+        .values()
+        .filter_map(|r| Some((r.id.clone(), r.resources.current?)))
+        .collect()
+}
+
+/// Requests from the command line of this or a later invocation, oldest
+/// first. Each request is returned once.
+#[tauri::command]
+#[specta::specta]
+pub fn take_launch_requests(requests: State<LaunchRequests>) -> Vec<LaunchRequest> {
+    std::mem::take(&mut *requests.0.lock())
+}
+
+/// The library game a request refers to.
+#[tauri::command]
+#[specta::specta]
+pub fn resolve_launch_request(
+    request: LaunchRequest,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    let games = state.games.lock();
+    let game = match &request {
+        LaunchRequest::Launch { game_id, .. } | LaunchRequest::ShowGame { game_id } => {
+            games.iter().find(|g| &g.id == game_id)
+        }
+        LaunchRequest::ShowVndb { vndb_id } => games
+            .iter()
+            .find(|g| g.vndb_id.as_deref() == Some(vndb_id.as_str())),
+    };
+    game.cloned()
+        .ok_or_else(|| AppError::NotFound("Game not found".into()))
 }
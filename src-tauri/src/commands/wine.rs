@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+use tokio::task;
+use uuid::Uuid;
+
+use crate::database::{
+    get_current_timestamp, get_default_prefixes_dir, save_games, save_wine_prefixes,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{GameMetadata, WineArch, WinePrefix};
+use crate::state::AppState;
+use crate::wine;
+
+fn find_prefix(state: &State<'_, AppState>, id: &str) -> AppResult<WinePrefix> {
+    state
+        .wine_prefixes
+        .lock()
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound("Wine prefix not found".into()))
+}
+
+fn store_prefix(state: &State<'_, AppState>, prefix: WinePrefix) -> AppResult<()> {
+    let mut prefixes = state.wine_prefixes.lock();
+    match prefixes.iter_mut().find(|p| p.id == prefix.id) {
+        Some(existing) => *existing = prefix,
+        None => prefixes.push(prefix),
+    }
+    save_wine_prefixes(&prefixes)
+}
+
+async fn blocking<T, F>(f: F) -> AppResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> AppResult<T> + Send + 'static,
+{
+    task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::ProcessLaunch(format!("Wine task failed: {}", e)))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_wine_prefixes(state: State<AppState>) -> Vec<WinePrefix> {
+    state.wine_prefixes.lock().clone()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_wine_prefix(
+    name: String,
+    path: Option<String>,
+    arch: WineArch,
+    wine_path: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<WinePrefix> {
+    wine::ensure_supported()?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("Prefix name cannot be empty".into()));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let path = match path.as_deref().map(str::trim) {
+        Some(p) if !p.is_empty() => p.to_string(),
+        _ => get_default_prefixes_dir()
+            .join(&id)
+            .to_string_lossy()
+            .to_string(),
+    };
+    if state.wine_prefixes.lock().iter().any(|p| p.path == path) {
+        return Err(AppError::Validation(format!(
+            "A prefix is already registered at {}",
+            path
+        )));
+    }
+    let created_by_launcher = wine::check_new_prefix_dir(Path::new(&path))?;
+
+    let prefix = WinePrefix {
+        id,
+        name,
+        path,
+        arch,
+        wine_path: wine_path.filter(|p| !p.trim().is_empty()),
+        locale: None,
+        dll_overrides: HashMap::new(),
+        created_at: get_current_timestamp(),
+        created_by_launcher,
+    };
+
+    let created = prefix.clone();
+    blocking(move || wine::create_prefix(&created)).await?;
+
+    store_prefix(&state, prefix.clone())?;
+    Ok(prefix)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_wine_prefix(id: String, delete_files: bool, state: State<AppState>) -> AppResult<()> {
+    let prefix = find_prefix(&state, &id)?;
+    if delete_files {
+        wine::delete_prefix_files(&prefix)?;
+    }
+
+    {
+        let mut games = state.games.lock();
+        let mut changed = false;
+        for game in games
+            .iter_mut()
+            .filter(|g| g.wine_prefix_id.as_deref() == Some(id.as_str()))
+        {
+            game.wine_prefix_id = None;
+            changed = true;
+        }
+        if changed {
+            save_games(&games)?;
+        }
+    }
+
+    let mut prefixes = state.wine_prefixes.lock();
+    prefixes.retain(|p| p.id != id);
+    save_wine_prefixes(&prefixes)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn assign_wine_prefix(
+    game_id: String,
+    prefix_id: Option<String>,
+    state: State<AppState>,
+) -> AppResult<GameMetadata> {
+    if let Some(ref prefix_id) = prefix_id {
+        find_prefix(&state, prefix_id)?;
+    }
+
+    let mut games = state.games.lock();
+    let game = games
+        .iter_mut()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| AppError::NotFound("Game not found".into()))?;
+    game.wine_prefix_id = prefix_id;
+    let updated = game.clone();
+    save_games(&games)?;
+    Ok(updated)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_prefix_dll_overrides(
+    id: String,
+    overrides: HashMap<String, String>,
+    state: State<'_, AppState>,
+) -> AppResult<WinePrefix> {
+    wine::ensure_supported()?;
+    let overrides = wine::validate_dll_overrides(&overrides)?;
+    let mut prefix = find_prefix(&state, &id)?;
+
+    let current = prefix.clone();
+    let applied = overrides.clone();
+    blocking(move || wine::apply_dll_overrides(&current, &applied)).await?;
+
+    prefix.dll_overrides = overrides;
+    store_prefix(&state, prefix.clone())?;
+    Ok(prefix)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_prefix_locale(
+    id: String,
+    locale: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<WinePrefix> {
+    wine::ensure_supported()?;
+    let locale = locale.filter(|l| !l.trim().is_empty());
+    if let Some(ref locale) = locale {
+        wine::validate_locale(locale)?;
+    }
+    let mut prefix = find_prefix(&state, &id)?;
+
+    let current = prefix.clone();
+    let applied = locale.clone();
+    blocking(move || wine::apply_locale(&current, applied.as_deref())).await?;
+
+    prefix.locale = locale;
+    store_prefix(&state, prefix.clone())?;
+    Ok(prefix)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn run_in_prefix(
+    id: String,
+    tool: String,
+    args: Vec<String>,
+    state: State<AppState>,
+) -> AppResult<()> {
+    wine::ensure_supported()?;
+    let prefix = find_prefix(&state, &id)?;
+    let child = wine::spawn_tool(&prefix, &tool, &args)?;
+
+    tauri::async_runtime::spawn_blocking(move || match child.wait_with_output() {
+        Ok(output) => log::info!(
+            "{} in prefix {} exited with {}: {} {}",
+            tool,
+            prefix.name,
+            output.status,
+            String::from_utf8_lossy(&output.stdout).trim(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => log::warn!("{} in prefix {} failed: {}", tool, prefix.name, e),
+    });
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use redb::{Database, TableDefinition};
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
@@ -6,7 +7,10 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use crate::error::{AppError, AppResult};
-use crate::models::{AppSettings, DailyPlaytimeData, GameMetadata};
+use crate::models::{
+    ActiveSession, AppSettings, DailyPlaytimeData, GameMetadata, PlaytimeAuditEntry,
+    SessionRecord, WinePrefix,
+};
 
 pub const VN_CACHE: TableDefinition<&str, &[u8]> = TableDefinition::new("vn_cache");
 pub const CHAR_CACHE: TableDefinition<&str, &[u8]> = TableDefinition::new("char_cache");
@@ -31,6 +35,30 @@ pub fn get_daily_playtime_path() -> PathBuf {
     get_data_dir().join("daily_playtime.json")
 }
 
+pub fn get_wine_prefixes_path() -> PathBuf {
+    get_data_dir().join("wine_prefixes.json")
+}
+
+pub fn get_active_sessions_path() -> PathBuf {
+    get_data_dir().join("active_sessions.json")
+}
+
+pub fn get_session_history_path() -> PathBuf {
+    get_data_dir().join("session_history.json")
+}
+
+pub fn get_playtime_audit_path() -> PathBuf {
+    get_data_dir().join("playtime_audit.json")
+}
+
+pub fn get_default_prefixes_dir() -> PathBuf {
+    get_data_dir().join("prefixes")
+}
+
+pub fn get_game_logs_dir() -> PathBuf {
+    get_data_dir().join("game_logs")
+}
+
 pub fn get_cache_db_path() -> PathBuf {
     get_data_dir().join("vndb_cache.redb")
 }
@@ -116,6 +144,78 @@ pub fn load_settings() -> AppSettings {
     }
 }
 
+pub fn load_wine_prefixes() -> Vec<WinePrefix> {
+    let path = get_wine_prefixes_path();
+    if path.exists() {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_wine_prefixes(prefixes: &[WinePrefix]) -> AppResult<()> {
+    let path = get_wine_prefixes_path();
+    let json = serde_json::to_string_pretty(prefixes)?;
+    atomic_write(&path, &json)
+}
+
+pub fn load_active_sessions() -> Vec<ActiveSession> {
+    let path = get_active_sessions_path();
+    if path.exists() {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_active_sessions(sessions: &[ActiveSession]) -> AppResult<()> {
+    let path = get_active_sessions_path();
+    let json = serde_json::to_string_pretty(sessions)?;
+    atomic_write(&path, &json)
+}
+
+pub fn load_session_history() -> Vec<SessionRecord> {
+    let path = get_session_history_path();
+    if path.exists() {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_session_history(history: &[SessionRecord]) -> AppResult<()> {
+    let path = get_session_history_path();
+    let json = serde_json::to_string_pretty(history)?;
+    atomic_write(&path, &json)
+}
+
+pub fn load_playtime_audit() -> Vec<PlaytimeAuditEntry> {
+    let path = get_playtime_audit_path();
+    if path.exists() {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_playtime_audit(audit: &[PlaytimeAuditEntry]) -> AppResult<()> {
+    let path = get_playtime_audit_path();
+    let json = serde_json::to_string_pretty(audit)?;
+    atomic_write(&path, &json)
+}
+
 pub fn load_daily_playtime() -> DailyPlaytimeData {
     let path = get_daily_playtime_path();
     if path.exists() {
@@ -134,24 +234,55 @@ pub fn save_daily_playtime(data: &DailyPlaytimeData) -> AppResult<()> {
     atomic_write(&path, &json)
 }
 
-pub fn record_daily_playtime(game_id: &str, minutes: u64) {
-    if minutes == 0 {
+pub fn record_daily_playtime(game_id: &str, days: &[(NaiveDate, u64)]) {
+    update_daily_playtime(game_id, days, |current, minutes| current + minutes);
+}
+
+/// Takes a session's minutes back out of the daily stats.
+pub fn remove_daily_playtime(game_id: &str, days: &[(NaiveDate, u64)]) {
+    update_daily_playtime(game_id, days, u64::saturating_sub);
+}
+
+fn update_daily_playtime(
+    game_id: &str,
+    days: &[(NaiveDate, u64)],
+    apply: impl Fn(u64, u64) -> u64,
+) {
+    if days.iter().all(|(_, minutes)| *minutes == 0) {
         return;
     }
 
-    let date_str = chrono::Local::now().format("%Y-%m-%d").to_string();
     let mut data = load_daily_playtime();
 
     let game_data = data
         .games
         .entry(game_id.to_string())
         .or_insert_with(HashMap::new);
-    let current = game_data.entry(date_str).or_insert(0);
-    *current += minutes;
+    for (date, minutes) in days.iter().filter(|(_, minutes)| *minutes > 0) {
+        let date = date.format("%Y-%m-%d").to_string();
+        let current = game_data.get(&date).copied().unwrap_or(0);
+        match apply(current, *minutes) {
+            0 => game_data.remove(&date),
+            total => game_data.insert(date, total),
+        };
+    }
 
     let _ = save_daily_playtime(&data);
 }
 
+/// Sets a game's total for one day and returns the previous total.
+pub fn set_daily_playtime(game_id: &str, date: &str, minutes: u64) -> AppResult<u64> {
+    let mut data = load_daily_playtime();
+    let game_data = data.games.entry(game_id.to_string()).or_default();
+    let before = if minutes == 0 {
+        game_data.remove(date)
+    } else {
+        game_data.insert(date.to_string(), minutes)
+    };
+    save_daily_playtime(&data)?;
+    Ok(before.unwrap_or(0))
+}
+
 pub fn get_current_timestamp() -> String {
     chrono::Local::now().to_rfc3339()
 }
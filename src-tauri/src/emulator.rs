@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult};
+use crate::launcher::{self, LaunchPlan};
+use crate::models::{EmulatorConfig, LaunchTarget};
+
+/// Image formats of the consoles VNs commonly ship on (PSP, Vita, PS2,
+/// Switch, ...). `add_local_game` treats these as ROMs rather than programs.
+const ROM_EXTENSIONS: &[&str] = &[
+    "iso", "cso", "chd", "pbp", "vpk", "nsp", "xci", "nsz", "xcz", "cue", "bin", "img", "mdf",
+    "3ds", "cia", "nds", "gba",
+];
+
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+pub fn is_rom(path: &Path) -> bool {
+    extension_of(path).is_some_and(|ext| ROM_EXTENSIONS.contains(&ext.as_str()))
+}
+
+pub fn validate_emulator(emulator: &EmulatorConfig) -> AppResult<EmulatorConfig> {
+    let name = emulator.name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Emulator name cannot be empty".into()));
+    }
+    let executable = emulator.executable.trim();
+    if executable.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Emulator {} has no executable",
+            name
+        )));
+    }
+    let args = emulator.args.trim();
+    if !launcher::split_command_line(args)?
+        .iter()
+        .any(|p| p.contains("{rom}"))
+    {
+        return Err(AppError::Validation(format!(
+            "Arguments for emulator {} must contain {{rom}}",
+            name
+        )));
+    }
+
+    let mut extensions: Vec<String> = Vec::new();
+    for ext in &emulator.extensions {
+        let ext = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+        if !ext.is_empty() && !extensions.contains(&ext) {
+            extensions.push(ext);
+        }
+    }
+
+    Ok(EmulatorConfig {
+        id: emulator.id.clone(),
+        name: name.to_string(),
+        executable: executable.to_string(),
+        args: args.to_string(),
+        extensions,
+    })
+}
+
+/// The ROM target for `path` if it looks like a console image, with the
+/// first emulator registered for its extension preselected.
+pub fn suggest_target(path: &Path, emulators: &[EmulatorConfig]) -> Option<LaunchTarget> {
+    if !is_rom(path) {
+        return None;
+    }
+    let ext = extension_of(path)?;
+    let emulator_id = emulators
+        .iter()
+        .find(|e| e.extensions.contains(&ext))
+        .map(|e| e.id.clone());
+    Some(LaunchTarget::Rom { emulator_id })
+}
+
+pub fn find<'a>(
+    emulators: &'a [EmulatorConfig],
+    emulator_id: Option<&str>,
+) -> AppResult<&'a EmulatorConfig> {
+    let id = emulator_id
+        .ok_or_else(|| AppError::Validation("Choose an emulator to run this game with".into()))?;
+    emulators
+        .iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| AppError::NotFound("Emulator not found".into()))
+}
+
+/// Runs the plan's ROM through `emulator`. Launch arguments of the game are
+/// passed to the emulator after its template.
+pub fn apply_emulator(plan: LaunchPlan, emulator: &EmulatorConfig) -> AppResult<LaunchPlan> {
+    let rom = plan.program.to_string_lossy().to_string();
+    let mut args: Vec<String> = launcher::split_command_line(&emulator.args)?
+        .into_iter()
+        .map(|part| part.replace("{rom}", &rom))
+        .collect();
+    args.extend(plan.args);
+
+    Ok(LaunchPlan {
+        program: PathBuf::from(&emulator.executable),
+        args,
+        ..plan
+    })
+}
@@ -0,0 +1,140 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::database::{get_current_timestamp, get_game_logs_dir};
+use crate::error::AppResult;
+
+/// Logs kept per game: the current one plus this many older ones.
+const KEPT_LOGS: usize = 4;
+/// How much of the end of a log `read_latest` returns.
+const READ_LIMIT: u64 = 256 * 1024;
+/// How large one run's log may grow. Past this it is cleared so a chatty
+/// game can't fill the disk, keeping its latest output.
+const MAX_LOG_BYTES: u64 = 16 * 1024 * 1024;
+
+/// `<id>.log` for the latest run, `<id>.1.log` for the one before, and so on.
+fn log_path(game_id: &str, index: usize) -> PathBuf {
+    let name: String = game_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let file = match index {
+        0 => format!("{}.log", name),
+        n => format!("{}.{}.log", name, n),
+    };
+    get_game_logs_dir().join(file)
+}
+
+pub fn latest_path(game_id: &str) -> PathBuf {
+    log_path(game_id, 0)
+}
+
+/// Where a run logs until its game has actually started.
+fn pending_path(game_id: &str) -> PathBuf {
+    latest_path(game_id).with_extension("new.log")
+}
+
+/// Opens a fresh log for a run that is about to be spawned, starting with a
+/// header naming the command line. It only replaces the latest log once
+/// [`keep`] is called, so a launch that fails doesn't rotate away the log of
+/// the last run that worked.
+pub fn create(game_id: &str, command_line: &str) -> io::Result<File> {
+    fs::create_dir_all(get_game_logs_dir())?;
+    let path = pending_path(game_id);
+    let _ = fs::remove_file(&path);
+    // Append mode keeps the game writing at the end after `enforce_limit`
+    // clears the file.
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "# {} {}", get_current_timestamp(), command_line)?;
+    Ok(file)
+}
+
+/// Rotates the game's older logs and makes the pending one the latest.
+pub fn keep(game_id: &str) -> io::Result<()> {
+    for index in (0..KEPT_LOGS).rev() {
+        let from = log_path(game_id, index);
+        if from.exists() {
+            fs::rename(&from, log_path(game_id, index + 1))?;
+        }
+    }
+    fs::rename(pending_path(game_id), latest_path(game_id))
+}
+
+/// Drops the pending log of a run that failed to start.
+pub fn discard(game_id: &str) {
+    let _ = fs::remove_file(pending_path(game_id));
+}
+
+/// Clears the latest log once its run has written more than
+/// [`MAX_LOG_BYTES`], leaving a note of what was dropped.
+pub fn enforce_limit(game_id: &str) {
+    let path = latest_path(game_id);
+    match clear_if_oversized(&path) {
+        Ok(true) => log::info!("Cleared oversized log {:?}", path),
+        Ok(false) => {}
+        Err(e) => log::warn!("Failed to clear oversized log {:?}: {}", path, e),
+    }
+}
+
+/// Returns whether the log at `path` was over the limit and got cleared.
+fn clear_if_oversized(path: &Path) -> io::Result<bool> {
+    let mut file = match OpenOptions::new().write(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if file.metadata()?.len() <= MAX_LOG_BYTES {
+        return Ok(false);
+    }
+    file.set_len(0)?;
+    writeln!(
+        file,
+        "# {} Earlier output dropped, the log passed {} MiB",
+        get_current_timestamp(),
+        MAX_LOG_BYTES / (1024 * 1024)
+    )?;
+    Ok(true)
+}
+
+/// The end of the latest log, or `None` if the game has none yet.
+pub fn read_latest(game_id: &str) -> AppResult<Option<String>> {
+    let path = latest_path(game_id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(READ_LIMIT)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_logs_past_the_cap() {
+        let path = std::env::temp_dir().join(format!("alka-log-{}.log", uuid::Uuid::new_v4()));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(&vec![b'x'; MAX_LOG_BYTES as usize]).unwrap();
+        assert!(!clear_if_oversized(&path).unwrap());
+        assert_eq!(fs::metadata(&path).unwrap().len(), MAX_LOG_BYTES);
+
+        file.write_all(b"x").unwrap();
+        assert!(clear_if_oversized(&path).unwrap());
+        let cleared = fs::read_to_string(&path).unwrap();
+        assert!(cleared.starts_with("# "));
+        assert!(cleared.contains("16 MiB"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn missing_log_is_left_alone() {
+        let path = std::env::temp_dir().join(format!("alka-log-{}.log", uuid::Uuid::new_v4()));
+        assert!(!clear_if_oversized(&path).unwrap());
+        assert!(!path.exists());
+    }
+}
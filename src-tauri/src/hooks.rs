@@ -0,0 +1,290 @@
+use std::fs;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{AppError, AppResult};
+use crate::models::HookCommands;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Values exported to hook commands as `ALKA_*` environment variables.
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    pub game_id: String,
+    pub title: String,
+    pub path: String,
+    pub session_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreLaunch,
+    PostExit,
+}
+
+impl HookStage {
+    fn name(self) -> &'static str {
+        match self {
+            HookStage::PreLaunch => "pre_launch",
+            HookStage::PostExit => "post_exit",
+        }
+    }
+
+    fn command(self, hooks: &HookCommands) -> Option<&str> {
+        let command = match self {
+            HookStage::PreLaunch => hooks.pre_launch.as_deref(),
+            HookStage::PostExit => hooks.post_exit.as_deref(),
+        };
+        command.map(str::trim).filter(|c| !c.is_empty())
+    }
+}
+
+/// Trims both commands, dropping ones that are left empty.
+pub fn normalize(hooks: HookCommands) -> HookCommands {
+    let clean = |c: Option<String>| c.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    HookCommands {
+        pre_launch: clean(hooks.pre_launch),
+        post_exit: clean(hooks.post_exit),
+    }
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// Everything a hook started: its process group on Unix, a job object on
+/// Windows. Killing it on timeout also ends what the shell spawned.
+struct HookProcesses {
+    #[cfg(not(windows))]
+    group: u32,
+    #[cfg(windows)]
+    job: windows_sys::Win32::Foundation::HANDLE,
+}
+
+impl HookProcesses {
+    #[cfg(not(windows))]
+    fn isolate(cmd: &mut Command) {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    /// The hook starts suspended so nothing it spawns can escape the job
+    /// before `attach` has assigned it.
+    #[cfg(windows)]
+    fn isolate(cmd: &mut Command) {
+        use std::os::windows::process::CommandExt;
+        use windows_sys::Win32::System::Threading::CREATE_SUSPENDED;
+        cmd.creation_flags(CREATE_SUSPENDED);
+    }
+
+    #[cfg(not(windows))]
+    fn attach(child: &Child) -> Self {
+        Self { group: child.id() }
+    }
+
+    #[cfg(windows)]
+    fn attach(child: &Child) -> Self {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+
+        // SAFETY: both handles are valid for the duration of the calls; a
+        // null job is handled by only using it when non-null.
+        let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if job.is_null()
+            || unsafe { AssignProcessToJobObject(job, child.as_raw_handle() as _) } == 0
+        {
+            log::warn!(
+                "Failed to put hook process {} in a job: {}",
+                child.id(),
+                std::io::Error::last_os_error()
+            );
+        }
+        resume_threads(child.id());
+        Self { job }
+    }
+
+    #[cfg(not(windows))]
+    fn kill(&self, child: &mut Child) {
+        // SAFETY: kill has no memory-safety preconditions; a negative pid
+        // signals the whole process group.
+        if unsafe { libc::kill(-(self.group as i32), libc::SIGKILL) } != 0 {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                log::warn!("Failed to kill hook process group {}: {}", self.group, err);
+            }
+        }
+        let _ = child.kill();
+    }
+
+    #[cfg(windows)]
+    fn kill(&self, child: &mut Child) {
+        use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+        if !self.job.is_null() {
+            // SAFETY: `job` is a job object handle owned by `self`.
+            unsafe { TerminateJobObject(self.job, 1) };
+        }
+        let _ = child.kill();
+    }
+}
+
+#[cfg(windows)]
+impl Drop for HookProcesses {
+    fn drop(&mut self) {
+        if !self.job.is_null() {
+            // SAFETY: `job` is owned by `self` and closed exactly once.
+            unsafe { windows_sys::Win32::Foundation::CloseHandle(self.job) };
+        }
+    }
+}
+
+/// Resumes the threads of a process started with `CREATE_SUSPENDED`. std
+/// does not expose the main thread handle, so they are found by snapshot.
+#[cfg(windows)]
+fn resume_threads(pid: u32) {
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows_sys::Win32::System::Threading::{OpenThread, ResumeThread, THREAD_SUSPEND_RESUME};
+
+    // SAFETY: the snapshot handle is checked before use and closed once;
+    // `entry` is a properly sized THREADENTRY32 owned by this frame.
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            log::warn!(
+                "Failed to resume hook process {}: {}",
+                pid,
+                std::io::Error::last_os_error()
+            );
+            return;
+        }
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut more = Thread32First(snapshot, &mut entry) != 0;
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                if !thread.is_null() {
+                    ResumeThread(thread);
+                    CloseHandle(thread);
+                }
+            }
+            more = Thread32Next(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+    }
+}
+
+/// Runs one hook through the platform shell, killing it after `timeout`.
+/// Output goes to a scratch file rather than pipes so a hook that leaves
+/// background processes behind can't keep us waiting on an open pipe.
+fn run_hook(
+    stage: HookStage,
+    command: &str,
+    ctx: &HookContext,
+    timeout: Duration,
+) -> AppResult<()> {
+    let output_path = std::env::temp_dir().join(format!("alka-hook-{}.log", uuid::Uuid::new_v4()));
+    let output = fs::File::create(&output_path)?;
+
+    let mut cmd = shell_command(command);
+    cmd.env("ALKA_HOOK", stage.name())
+        .env("ALKA_GAME_ID", &ctx.game_id)
+        .env("ALKA_GAME_TITLE", &ctx.title)
+        .env("ALKA_GAME_PATH", &ctx.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(output.try_clone()?))
+        .stderr(Stdio::from(output));
+    if let Some(minutes) = ctx.session_minutes {
+        cmd.env("ALKA_SESSION_MINUTES", minutes.to_string());
+    }
+    HookProcesses::isolate(&mut cmd);
+
+    log::info!(
+        "Running {} hook for {}: {}",
+        stage.name(),
+        ctx.title,
+        command
+    );
+    let mut child = cmd.spawn().map_err(|e| {
+        AppError::ProcessLaunch(format!("Failed to run {} hook: {}", stage.name(), e))
+    })?;
+    let processes = HookProcesses::attach(&child);
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            processes.kill(&mut child);
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let captured = fs::read_to_string(&output_path).unwrap_or_default();
+    let _ = fs::remove_file(&output_path);
+    if !captured.trim().is_empty() {
+        log::info!("{} hook output:\n{}", stage.name(), captured.trim_end());
+    }
+
+    match status {
+        Some(status) if status.success() => Ok(()),
+        Some(status) => Err(AppError::ProcessLaunch(format!(
+            "{} hook exited with {}",
+            stage.name(),
+            status
+        ))),
+        None => Err(AppError::ProcessLaunch(format!(
+            "{} hook timed out after {}s",
+            stage.name(),
+            timeout.as_secs()
+        ))),
+    }
+}
+
+/// Runs the global then the per-game pre-launch hook; the first failure
+/// aborts the launch.
+pub fn run_pre_launch(
+    global: &HookCommands,
+    game: &HookCommands,
+    ctx: &HookContext,
+    timeout: Duration,
+) -> AppResult<()> {
+    for hooks in [global, game] {
+        if let Some(command) = HookStage::PreLaunch.command(hooks) {
+            run_hook(HookStage::PreLaunch, command, ctx, timeout)?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs the per-game then the global post-exit hook. Failures are only
+/// logged since the session is already over.
+pub fn run_post_exit(
+    global: &HookCommands,
+    game: &HookCommands,
+    ctx: &HookContext,
+    timeout: Duration,
+) {
+    for hooks in [game, global] {
+        if let Some(command) = HookStage::PostExit.command(hooks) {
+            if let Err(e) = run_hook(HookStage::PostExit, command, ctx, timeout) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+#[cfg(not(windows))]
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::models::{ExcludedSpan, ExclusionReason, IdleSourceKind};
+
+/// Combined CPU usage (percent of one core) below which a game counts as idle.
+const CPU_IDLE_PERCENT: f32 = 1.0;
+
+/// Tells how long a session has been idle. Implementations that watch user
+/// input ignore the session; ones that watch the game itself use its pids.
+/// `None` means the source can't tell right now, which counts as active.
+pub trait IdleSource: Send {
+    fn idle_for(&mut self, session: &str, pids: &[u32]) -> Option<Duration>;
+}
+
+/// Time since the last keyboard or mouse input, from whatever the desktop
+/// exposes.
+pub struct InputIdleSource {
+    #[cfg_attr(windows, allow(dead_code))]
+    method: InputMethod,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(windows, allow(dead_code))]
+enum InputMethod {
+    LastInputInfo,
+    XPrintIdle,
+    MutterIdleMonitor,
+    ScreenSaver,
+}
+
+impl InputIdleSource {
+    /// Finds a working way to read input idle time, if there is one.
+    pub fn detect() -> Option<Self> {
+        if cfg!(windows) {
+            return Some(Self {
+                method: InputMethod::LastInputInfo,
+            });
+        }
+        [
+            InputMethod::MutterIdleMonitor,
+            InputMethod::ScreenSaver,
+            InputMethod::XPrintIdle,
+        ]
+        .into_iter()
+        .map(|method| Self { method })
+        .find(|source| source.query().is_some())
+    }
+
+    #[cfg(windows)]
+    fn query(&self) -> Option<Duration> {
+        use windows_sys::Win32::System::SystemInformation::GetTickCount;
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        // SAFETY: `info` is a properly sized LASTINPUTINFO owned by this frame.
+        if unsafe { GetLastInputInfo(&mut info) } == 0 {
+            return None;
+        }
+        let now = unsafe { GetTickCount() };
+        Some(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+    }
+
+    #[cfg(not(windows))]
+    fn query(&self) -> Option<Duration> {
+        let output = match self.method {
+            InputMethod::LastInputInfo => return None,
+            InputMethod::XPrintIdle => run(&mut Command::new("xprintidle"))?,
+            InputMethod::MutterIdleMonitor => run(Command::new("gdbus").args([
+                "call",
+                "--session",
+                "--dest",
+                "org.gnome.Mutter.IdleMonitor",
+                "--object-path",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "--method",
+                "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ]))?,
+            InputMethod::ScreenSaver => run(Command::new("gdbus").args([
+                "call",
+                "--session",
+                "--dest",
+                "org.freedesktop.ScreenSaver",
+                "--object-path",
+                "/org/freedesktop/ScreenSaver",
+                "--method",
+                "org.freedesktop.ScreenSaver.GetSessionIdleTime",
+            ]))?,
+        };
+        parse_millis(&output).map(Duration::from_millis)
+    }
+}
+
+impl IdleSource for InputIdleSource {
+    fn idle_for(&mut self, _session: &str, _pids: &[u32]) -> Option<Duration> {
+        self.query()
+    }
+}
+
+#[cfg(not(windows))]
+fn run(cmd: &mut Command) -> Option<String> {
+    let output = cmd
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Last number in tool output such as `1234` or gdbus' `(uint64 1234,)`.
+#[cfg(not(windows))]
+fn parse_millis(output: &str) -> Option<u64> {
+    output
+        .rsplit(|c: char| !c.is_ascii_digit())
+        .find(|part| !part.is_empty())
+        .and_then(|part| part.parse().ok())
+}
+
+/// Treats a game as idle while its processes use next to no CPU, for
+/// desktops where input idle time isn't available.
+#[derive(Default)]
+pub struct CpuIdleSource {
+    sys: System,
+    quiet_since: HashMap<String, Instant>,
+}
+
+impl IdleSource for CpuIdleSource {
+    fn idle_for(&mut self, session: &str, pids: &[u32]) -> Option<Duration> {
+        if pids.is_empty() {
+            return None;
+        }
+        let pids: Vec<Pid> = pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&pids),
+            true,
+            ProcessRefreshKind::nothing().with_cpu(),
+        );
+        let usage: f32 = pids
+            .iter()
+            .filter_map(|pid| self.sys.process(*pid))
+            .map(|p| p.cpu_usage())
+            .sum();
+
+        if usage >= CPU_IDLE_PERCENT {
+            self.quiet_since.remove(session);
+            return Some(Duration::ZERO);
+        }
+        let since = self
+            .quiet_since
+            .entry(session.to_string())
+            .or_insert_with(Instant::now);
+        Some(since.elapsed())
+    }
+}
+
+/// Never idle; used when idle detection is turned off.
+pub struct NoIdleSource;
+
+impl IdleSource for NoIdleSource {
+    fn idle_for(&mut self, _session: &str, _pids: &[u32]) -> Option<Duration> {
+        None
+    }
+}
+
+/// Builds the idle source for a setting, falling back to CPU usage when no
+/// input idle time is available.
+pub fn create_source(kind: IdleSourceKind) -> Box<dyn IdleSource> {
+    match kind {
+        IdleSourceKind::Off => Box::new(NoIdleSource),
+        IdleSourceKind::Cpu => Box::new(CpuIdleSource::default()),
+        IdleSourceKind::Input | IdleSourceKind::Auto => match InputIdleSource::detect() {
+            Some(source) => Box::new(source),
+            None if kind == IdleSourceKind::Auto => {
+                log::info!("No input idle time available, using CPU usage for idle detection");
+                Box::new(CpuIdleSource::default())
+            }
+            None => {
+                log::warn!("No input idle time available, idle detection disabled");
+                Box::new(NoIdleSource)
+            }
+        },
+    }
+}
+
+/// Advances one session's idle state given how long it has been idle at
+/// `now`. An idle segment opens once `idle` reaches `threshold`, backdated to
+/// when activity stopped, and is returned as a finished span when activity
+/// resumes.
+pub fn update_idle(
+    idle_since: &mut Option<u64>,
+    started_at: u64,
+    now: u64,
+    idle: Option<Duration>,
+    threshold: Duration,
+) -> Option<ExcludedSpan> {
+    let idle = idle.unwrap_or_default();
+    if idle >= threshold {
+        idle_since.get_or_insert(now.saturating_sub(idle.as_secs()).max(started_at));
+        return None;
+    }
+
+    let start = idle_since.take()?;
+    let end = now.saturating_sub(idle.as_secs()).max(start);
+    Some(ExcludedSpan {
+        start,
+        end,
+        reason: ExclusionReason::Idle,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(300);
+    const STARTED_AT: u64 = 1_000;
+
+    /// Replays scripted idle readings, one per tick.
+    struct FakeIdleSource(std::vec::IntoIter<Option<u64>>);
+
+    impl FakeIdleSource {
+        fn new(readings: &[Option<u64>]) -> Self {
+            Self(Vec::from(readings).into_iter())
+        }
+    }
+
+    impl IdleSource for FakeIdleSource {
+        fn idle_for(&mut self, _session: &str, _pids: &[u32]) -> Option<Duration> {
+            self.0.next().flatten().map(Duration::from_secs)
+        }
+    }
+
+    /// Ticks once per `(now, reading)` pair like the session ticker does,
+    /// returning the finished spans and the open idle segment.
+    fn replay(ticks: &[(u64, Option<u64>)]) -> (Vec<ExcludedSpan>, Option<u64>) {
+        let readings: Vec<Option<u64>> = ticks.iter().map(|(_, idle)| *idle).collect();
+        let mut source = FakeIdleSource::new(&readings);
+        let mut idle_since = None;
+        let mut spans = Vec::new();
+        for (now, _) in ticks {
+            let idle = source.idle_for("session", &[]);
+            spans.extend(update_idle(
+                &mut idle_since,
+                STARTED_AT,
+                *now,
+                idle,
+                THRESHOLD,
+            ));
+        }
+        (spans, idle_since)
+    }
+
+    #[test]
+    fn stays_active_below_threshold() {
+        let (spans, idle_since) =
+            replay(&[(1_100, Some(0)), (1_200, Some(100)), (1_299, Some(199))]);
+        assert!(spans.is_empty());
+        assert_eq!(idle_since, None);
+    }
+
+    #[test]
+    fn crossing_threshold_backdates_to_last_activity() {
+        let (spans, idle_since) = replay(&[(1_300, Some(100)), (1_500, Some(300))]);
+        assert!(spans.is_empty());
+        assert_eq!(idle_since, Some(1_200));
+    }
+
+    #[test]
+    fn idle_segment_never_starts_before_the_session() {
+        let (_, idle_since) = replay(&[(1_100, Some(3_600))]);
+        assert_eq!(idle_since, Some(STARTED_AT));
+    }
+
+    #[test]
+    fn returning_from_idle_closes_the_span() {
+        let (spans, idle_since) =
+            replay(&[(1_500, Some(300)), (1_800, Some(600)), (1_805, Some(2))]);
+        assert_eq!(idle_since, None);
+        assert_eq!(spans.len(), 1);
+        assert_eq!((spans[0].start, spans[0].end), (1_200, 1_803));
+        assert_eq!(spans[0].reason, ExclusionReason::Idle);
+    }
+
+    #[test]
+    fn unknown_idle_time_counts_as_active() {
+        let (spans, idle_since) = replay(&[(1_500, Some(300)), (1_600, None)]);
+        assert_eq!(idle_since, None);
+        assert_eq!((spans[0].start, spans[0].end), (1_200, 1_600));
+    }
+
+    #[test]
+    fn cpu_source_needs_processes() {
+        assert_eq!(CpuIdleSource::default().idle_for("session", &[]), None);
+        assert_eq!(
+            create_source(IdleSourceKind::Off).idle_for("session", &[1]),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cpu_source_counts_quiet_process_as_idle() {
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        let mut source = CpuIdleSource::default();
+        let first = source.idle_for("session", &[child.id()]);
+        std::thread::sleep(Duration::from_millis(50));
+        let second = source.idle_for("session", &[child.id()]);
+        let _ = child.kill();
+        let _ = child.wait();
+
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert!(
+            second >= Duration::from_millis(50),
+            "quiet for {:?}",
+            second
+        );
+        assert!(second > first);
+    }
+}
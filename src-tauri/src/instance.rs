@@ -0,0 +1,207 @@
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::models::LaunchRequest;
+use crate::state::LaunchRequests;
+
+/// Reads `--launch <id>` and `--game <id>` (or their `=` forms) from a
+/// command line, without the program name.
+pub fn parse_args<I>(args: I) -> Vec<LaunchRequest>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut requests = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let make: fn(String) -> LaunchRequest = match flag.as_str() {
+            "--launch" => |game_id| LaunchRequest::Launch {
+                game_id,
+                from_link: false,
+            },
+            "--game" => |game_id| LaunchRequest::ShowGame { game_id },
+            _ => continue,
+        };
+        match value.or_else(|| args.next()) {
+            Some(id) if !id.trim().is_empty() => requests.push(make(id.trim().to_string())),
+            _ => log::warn!("{} needs a game id", flag),
+        }
+    }
+    requests
+}
+
+pub const URL_SCHEME: &str = "alka";
+
+/// Reads `alka://launch/<id>`, `alka://game/<id>` and `alka://vndb/v1234`.
+pub fn parse_url(url: &Url) -> Option<LaunchRequest> {
+    if url.scheme() != URL_SCHEME {
+        return None;
+    }
+    let id = url.path().trim_matches('/');
+    if id.is_empty() || id.contains('/') {
+        return None;
+    }
+    let id = id.to_string();
+    match url.host_str()? {
+        "launch" => Some(LaunchRequest::Launch {
+            game_id: id,
+            from_link: true,
+        }),
+        "game" => Some(LaunchRequest::ShowGame { game_id: id }),
+        "vndb" => {
+            let id = id.to_ascii_lowercase();
+            let number = id.strip_prefix('v').unwrap_or(&id);
+            if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            Some(LaunchRequest::ShowVndb {
+                vndb_id: format!("v{}", number),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn parse_urls(urls: Vec<Url>) -> Vec<LaunchRequest> {
+    urls.into_iter()
+        .filter_map(|url| {
+            let request = parse_url(&url);
+            if request.is_none() {
+                log::warn!("Ignoring unsupported link {}", url);
+            }
+            request
+        })
+        .collect()
+}
+
+/// Hands requests to the frontend, which picks them up with
+/// `take_launch_requests` once it is listening.
+pub fn queue(app: &AppHandle, requests: Vec<LaunchRequest>) {
+    if requests.is_empty() {
+        return;
+    }
+    app.state::<LaunchRequests>().0.lock().extend(requests);
+    let _ = app.emit("launch-requests", ());
+}
+
+fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Called in the running instance when the app is started again. Links
+/// passed that way arrive through [`listen_for_links`] instead.
+pub fn on_second_instance(app: &AppHandle, args: Vec<String>) {
+    focus_main_window(app);
+    queue(app, parse_args(args.into_iter().skip(1)));
+}
+
+/// Queues the link the app was started with and any opened while it runs.
+pub fn listen_for_links(app: &AppHandle) {
+    // Installers register the scheme; AppImages and dev builds do it here.
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register {}:// links: {}", URL_SCHEME, e);
+    }
+
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        queue(app, parse_urls(urls));
+    }
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        focus_main_window(&handle);
+        queue(&handle, parse_urls(event.urls()));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<LaunchRequest> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    fn url(url: &str) -> Option<LaunchRequest> {
+        parse_url(&Url::parse(url).unwrap())
+    }
+
+    fn launch(game_id: &str, from_link: bool) -> LaunchRequest {
+        LaunchRequest::Launch {
+            game_id: game_id.into(),
+            from_link,
+        }
+    }
+
+    #[test]
+    fn reads_launch_and_game_flags() {
+        assert_eq!(
+            args(&[
+                "--launch",
+                "abc",
+                "--game=def",
+                "--verbose",
+                "--launch= ghi "
+            ]),
+            vec![
+                launch("abc", false),
+                LaunchRequest::ShowGame {
+                    game_id: "def".into()
+                },
+                launch("ghi", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_flags_without_an_id() {
+        assert!(args(&["--launch"]).is_empty());
+        assert!(args(&["--game="]).is_empty());
+        assert!(args(&["--launch", "  "]).is_empty());
+        assert!(args(&["abc", "--unknown=abc"]).is_empty());
+    }
+
+    #[test]
+    fn reads_links() {
+        assert_eq!(url("alka://launch/abc"), Some(launch("abc", true)));
+        assert_eq!(
+            url("alka://game/abc/"),
+            Some(LaunchRequest::ShowGame {
+                game_id: "abc".into()
+            })
+        );
+        assert_eq!(
+            url("alka://vndb/V17"),
+            Some(LaunchRequest::ShowVndb {
+                vndb_id: "v17".into()
+            })
+        );
+        assert_eq!(
+            url("alka://vndb/17"),
+            Some(LaunchRequest::ShowVndb {
+                vndb_id: "v17".into()
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_links() {
+        for link in [
+            "https://launch/abc",
+            "alka://launch/",
+            "alka://launch/a/b",
+            "alka://settings/abc",
+            "alka://vndb/v",
+            "alka://vndb/v17x",
+            "alka://vndb/r17",
+        ] {
+            assert_eq!(url(link), None, "{} should be rejected", link);
+        }
+    }
+}
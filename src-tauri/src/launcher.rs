@@ -0,0 +1,559 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{GameMetadata, LaunchOptions, LaunchProfile, LaunchTarget, ProcessPriority};
+
+/// Everything needed to spawn a game, resolved from its metadata.
+#[derive(Debug, Clone)]
+pub struct LaunchPlan {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    pub env: Vec<(String, String)>,
+    /// Applied as the priority class on Windows; Unix uses a `nice` wrapper.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub priority: ProcessPriority,
+}
+
+impl LaunchPlan {
+    /// Shell-like rendering of the full command line, for logs and previews.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program.to_string_lossy().to_string())
+            .chain(self.args.iter().cloned())
+            .map(|part| {
+                if part.is_empty() || part.contains(char::is_whitespace) {
+                    format!("\"{}\"", part)
+                } else {
+                    part
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn set_default_env(&mut self, key: &str, value: &str) {
+        if !self.env.iter().any(|(k, _)| k == key) {
+            self.env.push((key.to_string(), value.to_string()));
+            self.env.sort();
+        }
+    }
+
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args).current_dir(&self.cwd);
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            use windows_sys::Win32::System::Threading::{
+                ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS,
+                IDLE_PRIORITY_CLASS,
+            };
+            let class = match self.priority {
+                ProcessPriority::Idle => Some(IDLE_PRIORITY_CLASS),
+                ProcessPriority::BelowNormal => Some(BELOW_NORMAL_PRIORITY_CLASS),
+                ProcessPriority::Normal => None,
+                ProcessPriority::AboveNormal => Some(ABOVE_NORMAL_PRIORITY_CLASS),
+                ProcessPriority::High => Some(HIGH_PRIORITY_CLASS),
+            };
+            if let Some(class) = class {
+                cmd.creation_flags(class);
+            }
+        }
+        cmd
+    }
+
+    /// Spawns the plan, sending stdout and stderr to `output` when given.
+    pub fn spawn(&self, output: Option<File>) -> AppResult<Child> {
+        let mut cmd = self.command();
+        if let Some(output) = output {
+            cmd.stdout(output.try_clone()?).stderr(output);
+        }
+        cmd.spawn().map_err(|e| {
+            use std::io::ErrorKind;
+            match e.kind() {
+                ErrorKind::NotFound => AppError::ProcessLaunch(format!(
+                    "Executable not found or invalid: {}",
+                    self.program.display()
+                )),
+                ErrorKind::PermissionDenied => AppError::ProcessLaunch(format!(
+                    "Permission denied: cannot execute {}",
+                    self.program.display()
+                )),
+                _ => AppError::ProcessLaunch(format!(
+                    "Failed to launch game: {} ({})",
+                    e,
+                    self.program.display()
+                )),
+            }
+        })
+    }
+}
+
+/// Splits a command template into arguments. Single and double quotes group
+/// words; backslashes are kept literally so Windows paths survive.
+pub fn split_command_line(line: &str) -> AppResult<Vec<String>> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    parts.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(AppError::Validation(format!(
+            "Unterminated quote in command: {}",
+            line
+        )));
+    }
+    if in_word {
+        parts.push(current);
+    }
+    Ok(parts)
+}
+
+/// Trims empty values and rejects options that can never be spawned.
+pub fn validate_launch_options(options: &LaunchOptions) -> AppResult<LaunchOptions> {
+    if options.args.iter().any(|a| a.contains('\0')) {
+        return Err(AppError::Validation(
+            "Launch arguments cannot contain NUL characters".into(),
+        ));
+    }
+
+    // Existence is checked at launch, so games on unplugged drives can
+    // still be edited.
+    let working_dir = match options.working_dir.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(dir) => Some(dir.to_string()),
+    };
+
+    for (key, value) in &options.env {
+        if key.trim().is_empty() {
+            return Err(AppError::Validation(
+                "Environment variable name cannot be empty".into(),
+            ));
+        }
+        if key.contains('=') || key.contains('\0') {
+            return Err(AppError::Validation(format!(
+                "Invalid environment variable name: {}",
+                key
+            )));
+        }
+        if value.contains('\0') {
+            return Err(AppError::Validation(format!(
+                "Environment variable {} contains a NUL character",
+                key
+            )));
+        }
+    }
+
+    Ok(LaunchOptions {
+        args: options.args.clone(),
+        working_dir,
+        env: options
+            .env
+            .iter()
+            .map(|(k, v)| (k.trim().to_string(), v.clone()))
+            .collect(),
+    })
+}
+
+/// Resolves which profile a launch uses: the requested one, else the game's
+/// default profile, else `None` for the game's main executable.
+pub fn resolve_profile<'a>(
+    game: &'a GameMetadata,
+    profile_id: Option<&str>,
+) -> AppResult<Option<&'a LaunchProfile>> {
+    if let Some(profile_id) = profile_id {
+        return game
+            .profiles
+            .iter()
+            .find(|p| p.id == profile_id)
+            .map(Some)
+            .ok_or_else(|| AppError::NotFound("Launch profile not found".into()));
+    }
+
+    match game.default_profile_id.as_deref() {
+        Some(default_id) => {
+            let profile = game.profiles.iter().find(|p| p.id == default_id);
+            if profile.is_none() {
+                log::warn!(
+                    "Default profile {} of {} no longer exists, using main executable",
+                    default_id,
+                    game.title
+                );
+            }
+            Ok(profile)
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn validate_profile(profile: &LaunchProfile) -> AppResult<LaunchProfile> {
+    let name = profile.name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Profile name cannot be empty".into()));
+    }
+    let path = profile.path.trim();
+    if path.is_empty() {
+        return Err(AppError::Validation("Profile path cannot be empty".into()));
+    }
+
+    let launch_options = validate_launch_options(&profile.launch_options)?;
+    let target = validate_target(&profile.target, path)?;
+    check_target_options(&target, &launch_options)?;
+
+    Ok(LaunchProfile {
+        id: profile.id.clone(),
+        name: name.to_string(),
+        path: path.to_string(),
+        launch_options,
+        target,
+    })
+}
+
+/// Whether `uri` starts with a scheme such as `steam:`. Single letters are
+/// left out so Windows drive letters don't count.
+fn has_uri_scheme(uri: &str) -> bool {
+    let Some((scheme, _)) = uri.split_once(':') else {
+        return false;
+    };
+    scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Checks a launch target against the path it applies to and trims a
+/// script's interpreter.
+pub fn validate_target(target: &LaunchTarget, path: &str) -> AppResult<LaunchTarget> {
+    match target {
+        LaunchTarget::Executable => Ok(LaunchTarget::Executable),
+        LaunchTarget::Script { interpreter } => {
+            let interpreter = interpreter
+                .as_deref()
+                .map(str::trim)
+                .filter(|i| !i.is_empty());
+            if let Some(interpreter) = interpreter {
+                if split_command_line(interpreter)?.is_empty() {
+                    return Err(AppError::Validation("Interpreter cannot be empty".into()));
+                }
+            }
+            Ok(LaunchTarget::Script {
+                interpreter: interpreter.map(str::to_string),
+            })
+        }
+        LaunchTarget::Uri => {
+            if !has_uri_scheme(path.trim()) {
+                return Err(AppError::Validation(format!("Not a URI: {}", path)));
+            }
+            Ok(LaunchTarget::Uri)
+        }
+        LaunchTarget::Rom { emulator_id } => Ok(LaunchTarget::Rom {
+            emulator_id: emulator_id
+                .as_deref()
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string),
+        }),
+    }
+}
+
+/// URIs are handed to the OS opener, which has no way to pass arguments on
+/// to the game.
+pub fn check_target_options(target: &LaunchTarget, options: &LaunchOptions) -> AppResult<()> {
+    if *target == LaunchTarget::Uri && !options.args.is_empty() {
+        return Err(AppError::Validation(
+            "URI launches can't take launch arguments".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// The launch target of a profile, or of the game's main path.
+pub fn target_of<'a>(game: &'a GameMetadata, profile: Option<&'a LaunchProfile>) -> &'a LaunchTarget {
+    profile.map(|p| &p.target).unwrap_or(&game.target)
+}
+
+/// Interpreter for a script, picked from its extension.
+fn default_interpreter(script: &Path) -> Vec<String> {
+    let extension = script
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let parts: &[&str] = match extension.as_str() {
+        "bat" | "cmd" => &["cmd", "/C"],
+        "ps1" => &["powershell", "-ExecutionPolicy", "Bypass", "-File"],
+        "py" if cfg!(windows) => &["python"],
+        "py" => &["python3"],
+        _ => &["sh"],
+    };
+    parts.iter().map(|p| p.to_string()).collect()
+}
+
+/// Program that opens URIs with their registered handler.
+fn uri_opener() -> &'static str {
+    if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    }
+}
+
+fn existing_working_dir(options: &LaunchOptions) -> AppResult<Option<PathBuf>> {
+    match options.working_dir.as_deref() {
+        Some(dir) if !Path::new(dir).is_dir() => Err(AppError::ProcessLaunch(format!(
+            "Working directory does not exist: {}",
+            dir
+        ))),
+        dir => Ok(dir.map(PathBuf::from)),
+    }
+}
+
+pub fn plan_launch(game: &GameMetadata, profile: Option<&LaunchProfile>) -> AppResult<LaunchPlan> {
+    let (path, options) = match profile {
+        Some(p) => (PathBuf::from(&p.path), &p.launch_options),
+        None => (PathBuf::from(&game.path), &game.launch_options),
+    };
+    let target = target_of(game, profile);
+
+    if *target == LaunchTarget::Uri {
+        check_target_options(target, options)?;
+        let options = validate_launch_options(options)?;
+        let working_dir = existing_working_dir(&options)?;
+        let mut env: Vec<(String, String)> = options.env.into_iter().collect();
+        env.sort();
+        return Ok(LaunchPlan {
+            program: PathBuf::from(uri_opener()),
+            args: vec![path.to_string_lossy().to_string()],
+            cwd: working_dir
+                .or_else(dirs::home_dir)
+                .unwrap_or_else(|| PathBuf::from(".")),
+            env,
+            priority: ProcessPriority::Normal,
+        });
+    }
+
+    if !path.exists() {
+        return Err(AppError::ProcessLaunch(format!(
+            "Game executable not found: {}",
+            path.display()
+        )));
+    }
+    if !path.is_file() {
+        return Err(AppError::ProcessLaunch(format!(
+            "Path is not a file: {}",
+            path.display()
+        )));
+    }
+
+    let options = validate_launch_options(options)?;
+    let cwd = match existing_working_dir(&options)? {
+        Some(dir) => dir,
+        None => path.parent().unwrap_or(&path).to_path_buf(),
+    };
+
+    let mut env: Vec<(String, String)> = options.env.into_iter().collect();
+    env.sort();
+
+    let (program, args) = match target {
+        LaunchTarget::Script { interpreter } => {
+            let mut parts = match interpreter {
+                Some(interpreter) => split_command_line(interpreter)?,
+                None => default_interpreter(&path),
+            };
+            parts.push(path.to_string_lossy().to_string());
+            parts.extend(options.args);
+            (PathBuf::from(parts.remove(0)), parts)
+        }
+        _ => (path, options.args),
+    };
+
+    Ok(LaunchPlan {
+        program,
+        args,
+        cwd,
+        env,
+        priority: ProcessPriority::Normal,
+    })
+}
+
+pub const JAPANESE_LOCALE: &str = "ja_JP.UTF-8";
+
+pub fn validate_locale_emulator_command(template: &str) -> AppResult<()> {
+    let parts = split_command_line(template)?;
+    if parts.is_empty() {
+        return Err(AppError::Validation(
+            "Locale emulator command cannot be empty".into(),
+        ));
+    }
+    if !parts.iter().any(|p| p.contains("{exe}")) {
+        return Err(AppError::Validation(
+            "Locale emulator command must contain {exe}".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Starts the plan in a Japanese locale. On Windows the game is wrapped in
+/// the configured Locale Emulator template (`{exe}` and `{args}` are
+/// substituted) and the returned path is the executable to keep tracking
+/// once the wrapper exits. Elsewhere the locale is set through the
+/// environment, which Wine also uses to pick its code page.
+pub fn apply_japanese_locale(
+    mut plan: LaunchPlan,
+    emulator_command: Option<&str>,
+) -> AppResult<(LaunchPlan, Option<PathBuf>)> {
+    if !cfg!(windows) {
+        plan.set_default_env("LANG", JAPANESE_LOCALE);
+        plan.set_default_env("LC_ALL", JAPANESE_LOCALE);
+        return Ok((plan, None));
+    }
+
+    let template = emulator_command
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| AppError::Validation("No locale emulator command is configured".into()))?;
+    validate_locale_emulator_command(template)?;
+
+    let exe = plan.program.to_string_lossy().to_string();
+    let mut parts = Vec::new();
+    let mut args_used = false;
+    for part in split_command_line(template)? {
+        if part == "{args}" {
+            parts.extend(plan.args.iter().cloned());
+            args_used = true;
+        } else {
+            parts.push(part.replace("{exe}", &exe));
+        }
+    }
+    if !args_used {
+        parts.extend(plan.args.iter().cloned());
+    }
+
+    let program = PathBuf::from(parts.remove(0));
+    let target = std::mem::replace(&mut plan.program, program);
+    plan.args = parts;
+
+    Ok((plan, Some(target)))
+}
+
+/// Trims wrapper commands, drops empty ones and checks that each parses.
+pub fn validate_wrappers(wrappers: &[String]) -> AppResult<Vec<String>> {
+    let mut valid = Vec::new();
+    for wrapper in wrappers.iter().map(|w| w.trim()).filter(|w| !w.is_empty()) {
+        split_command_line(wrapper)?;
+        valid.push(wrapper.to_string());
+    }
+    Ok(valid)
+}
+
+/// Nice level for a priority; raising it above normal needs privileges, and
+/// `nice` runs the game anyway if that fails.
+fn nice_level(priority: ProcessPriority) -> Option<i32> {
+    match priority {
+        ProcessPriority::Idle => Some(19),
+        ProcessPriority::BelowNormal => Some(10),
+        ProcessPriority::Normal => None,
+        ProcessPriority::AboveNormal => Some(-5),
+        ProcessPriority::High => Some(-10),
+    }
+}
+
+/// Runs the plan through `wrappers`, outermost first, at `priority`. On Unix
+/// a non-default priority becomes a `nice` call around all the wrappers.
+pub fn apply_wrappers(
+    mut plan: LaunchPlan,
+    wrappers: &[String],
+    priority: ProcessPriority,
+) -> AppResult<LaunchPlan> {
+    let mut parts = Vec::new();
+    if !cfg!(windows) {
+        if let Some(level) = nice_level(priority) {
+            parts.extend(["nice".to_string(), "-n".to_string(), level.to_string()]);
+        }
+    }
+    for wrapper in wrappers {
+        parts.extend(split_command_line(wrapper)?);
+    }
+    plan.priority = priority;
+    if parts.is_empty() {
+        return Ok(plan);
+    }
+
+    parts.push(plan.program.to_string_lossy().to_string());
+    parts.append(&mut plan.args);
+    plan.program = PathBuf::from(parts.remove(0));
+    plan.args = parts;
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Vec<String> {
+        split_command_line(line).unwrap()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(split("  wine\tgame.exe  -w "), ["wine", "game.exe", "-w"]);
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn quotes_group_words() {
+        assert_eq!(
+            split(r#"LEProc.exe -run "C:\Program Files\Game\game.exe" %command%"#),
+            [
+                "LEProc.exe",
+                "-run",
+                r"C:\Program Files\Game\game.exe",
+                "%command%"
+            ]
+        );
+        assert_eq!(split(r#"--name="My Game"'s'"#), ["--name=My Games"]);
+        assert_eq!(split(r#"'say "hi"' "it's""#), [r#"say "hi""#, "it's"]);
+        assert_eq!(split(r#"a "" b"#), ["a", "", "b"]);
+    }
+
+    #[test]
+    fn backslashes_are_literal() {
+        assert_eq!(split(r"C:\games\a\ b"), [r"C:\games\a\", "b"]);
+        assert!(split_command_line(r#"echo \"quoted"#).is_err());
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        assert!(split_command_line(r#"game.exe "unterminated"#).is_err());
+        assert!(split_command_line("it's").is_err());
+    }
+}
@@ -1,20 +1,39 @@
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use tauri::Manager;
 
+mod clock;
 mod commands;
 mod database;
 mod discord;
+mod emulator;
 mod error;
+mod game_log;
+mod hooks;
+mod idle;
+mod instance;
+mod launcher;
 mod models;
+mod monitor;
+mod playtime;
+mod process;
+mod runner;
+mod sandbox;
+mod scanner;
+mod session;
 mod state;
+mod watcher;
+mod wine;
 
 use commands::*;
-use database::{create_cache_db, create_http_client, load_games, load_settings};
+use database::{
+    create_cache_db, create_http_client, load_games, load_playtime_audit, load_settings,
+    load_wine_prefixes,
+};
 use models::*;
-use state::AppState;
+use state::{AppState, LaunchRequests};
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
+fn load_state() -> AppState {
     let db = create_cache_db();
 
     let games = match load_games() {
@@ -25,24 +44,37 @@ pub fn run() {
         }
     };
 
+    let session_history = session::load_history(&games);
     let http_client = create_http_client();
 
-    let state = AppState {
+    AppState {
         games: Mutex::new(games),
-        running_game: Mutex::new(None),
+        running_games: Mutex::new(HashMap::new()),
+        launching: Mutex::new(HashSet::new()),
+        session_history: Mutex::new(session_history),
+        playtime_audit: Mutex::new(load_playtime_audit()),
         settings: Mutex::new(load_settings()),
+        wine_prefixes: Mutex::new(load_wine_prefixes()),
         vn_mem_cache: Mutex::new(HashMap::new()),
         char_mem_cache: Mutex::new(HashMap::new()),
         http_client,
         db,
         discord_rpc: discord::DiscordRpc::new(),
-    };
+    }
+}
 
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
     let builder = tauri_specta::Builder::<tauri::Wry>::new()
         .commands(tauri_specta::collect_commands![
             init_app,
+            take_launch_requests,
+            resolve_launch_request,
             get_all_games,
             add_local_game,
+            add_uri_game,
+            scan_library,
+            import_scanned_games,
             remove_game,
             update_game,
             search_vndb,
@@ -60,12 +92,53 @@ pub fn run() {
             vndb_set_vote,
             vndb_remove_vote,
             launch_game,
+            launch_game_profile,
             stop_tracking,
+            force_stop_game,
+            get_game_log,
+            preview_launch,
             poll_running_game,
             get_elapsed_time,
+            get_resource_usage,
+            attach_to_process,
+            list_sessions,
+            add_manual_session,
+            edit_session,
+            delete_session,
+            adjust_daily_playtime,
+            list_playtime_audit,
+            revert_playtime_change,
             set_game_hidden,
+            set_launch_options,
+            add_launch_profile,
+            update_launch_profile,
+            remove_launch_profile,
+            set_default_profile,
+            set_game_runner,
+            set_game_japanese_locale,
+            set_game_hooks,
+            set_track_executable,
+            set_game_wrappers,
+            set_game_sandbox,
+            set_launch_target,
             set_discord_rpc_enabled,
             set_discord_rpc_buttons,
+            set_default_runner,
+            set_locale_emulator_command,
+            set_global_hooks,
+            set_global_wrappers,
+            set_emulators,
+            set_auto_detect_games,
+            set_idle_detection,
+            set_day_start_hour,
+            set_resource_sampling,
+            list_wine_prefixes,
+            create_wine_prefix,
+            delete_wine_prefix,
+            assign_wine_prefix,
+            set_prefix_dll_overrides,
+            set_prefix_locale,
+            run_in_prefix,
         ])
         .typ::<GameMetadata>()
         .typ::<DailyPlaytimeData>()
@@ -91,14 +164,20 @@ pub fn run() {
         )
         .expect("Failed to export TypeScript bindings");
 
-    tauri::Builder::default()
+    let app = tauri::Builder::default()
+        // Ready before the single-instance plugin can forward requests.
+        .manage(LaunchRequests::default())
+        // Must come first so a second launch exits before touching any data.
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            instance::on_second_instance(app, args);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .invoke_handler(builder.invoke_handler())
-        .manage(state)
         .setup(move |app| {
             builder.mount_events(app);
             app.handle().plugin(
@@ -106,8 +185,19 @@ pub fn run() {
                     .level(log::LevelFilter::Info)
                     .build(),
             )?;
+            session::recover(app.handle());
+            session::spawn_ticker(app.handle().clone());
+            watcher::spawn(app.handle().clone());
+            monitor::spawn(app.handle().clone());
+            instance::queue(app.handle(), instance::parse_args(std::env::args().skip(1)));
+            instance::listen_for_links(app.handle());
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application");
+
+    // Plugins are initialized by now, so a second instance has already
+    // handed its arguments over and exited without loading any data.
+    app.manage(load_state());
+    app.run(|_, _| {});
 }
@@ -1,3 +1,4 @@
+use chrono::{NaiveDate, TimeZone};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Instant;
@@ -15,6 +16,241 @@ pub struct GameMetadata {
     pub last_played: Option<String>,
     #[serde(default)]
     pub is_hidden: bool,
+    #[serde(default)]
+    pub launch_options: LaunchOptions,
+    #[serde(default)]
+    pub profiles: Vec<LaunchProfile>,
+    #[serde(default)]
+    pub default_profile_id: Option<String>,
+    #[serde(default)]
+    pub runner: Option<RunnerConfig>,
+    #[serde(default)]
+    pub wine_prefix_id: Option<String>,
+    #[serde(default)]
+    pub japanese_locale: bool,
+    #[serde(default)]
+    pub hooks: HookCommands,
+    /// Executable name to wait for when the launcher detaches the game
+    /// from its own process tree.
+    #[serde(default)]
+    pub track_executable: Option<String>,
+    /// Commands the game is launched through, outermost first, e.g.
+    /// `gamescope -W 1920 --`. Run inside the global wrappers.
+    #[serde(default)]
+    pub wrappers: Vec<String>,
+    #[serde(default = "default_true")]
+    pub use_global_wrappers: bool,
+    /// Overrides the global process priority.
+    #[serde(default)]
+    pub priority: Option<ProcessPriority>,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub target: LaunchTarget,
+}
+
+impl GameMetadata {
+    pub fn new(title: String, path: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            title,
+            path,
+            vndb_id: None,
+            cover_url: None,
+            play_time: 0,
+            is_finished: false,
+            last_played: None,
+            is_hidden: false,
+            launch_options: LaunchOptions::default(),
+            profiles: Vec::new(),
+            default_profile_id: None,
+            runner: None,
+            wine_prefix_id: None,
+            japanese_locale: false,
+            hooks: HookCommands::default(),
+            track_executable: None,
+            wrappers: Vec::new(),
+            use_global_wrappers: true,
+            priority: None,
+            sandbox: SandboxConfig::default(),
+            target: LaunchTarget::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
+pub struct LaunchOptions {
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct LaunchProfile {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub launch_options: LaunchOptions,
+    #[serde(default)]
+    pub target: LaunchTarget,
+}
+
+/// What a game or profile `path` points at.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type")]
+pub enum LaunchTarget {
+    #[default]
+    Executable,
+    /// A script run through `interpreter`, e.g. `bash` or `python3`, or one
+    /// picked from its extension when unset.
+    Script { interpreter: Option<String> },
+    /// A URI such as `steam://rungameid/...` handed to the OS opener. The
+    /// session follows the game's "track executable" once it shows up.
+    Uri,
+    /// A ROM or disc image run through one of the emulators in settings.
+    Rom { emulator_id: Option<String> },
+}
+
+/// Something the command line or an `alka://` link asks the running
+/// launcher to do, e.g. `--launch <game id>` passed to a second invocation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type")]
+pub enum LaunchRequest {
+    Launch {
+        game_id: String,
+        /// Came from an `alka://` link, which any web page can open, so the
+        /// frontend asks before launching.
+        #[serde(default)]
+        from_link: bool,
+    },
+    ShowGame { game_id: String },
+    /// Opens the library game linked to a VNDB id such as `v1234`.
+    ShowVndb { vndb_id: String },
+}
+
+/// An emulator console games can be launched through. `args` is a command
+/// line template where `{rom}` is replaced with the game's image.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct EmulatorConfig {
+    pub id: String,
+    pub name: String,
+    pub executable: String,
+    #[serde(default = "default_emulator_args")]
+    pub args: String,
+    /// ROM extensions (without the dot) this emulator is suggested for.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+fn default_emulator_args() -> String {
+    "{rom}".into()
+}
+
+/// Shell commands run before a game is spawned and after it exits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct HookCommands {
+    #[serde(default)]
+    pub pre_launch: Option<String>,
+    #[serde(default)]
+    pub post_exit: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum SandboxTool {
+    /// Bubblewrap if installed, else firejail.
+    #[default]
+    Auto,
+    Bubblewrap,
+    Firejail,
+}
+
+/// Runs a game cut off from the network and the home directory (Linux only).
+/// The game's directory and Wine prefix are always reachable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct SandboxConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub tool: SandboxTool,
+    #[serde(default)]
+    pub allow_network: bool,
+    /// Extra directories the game may write to, e.g. its save directory.
+    #[serde(default)]
+    pub allowed_paths: Vec<String>,
+}
+
+/// What launching a game would run, for debugging launch settings.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct LaunchPreview {
+    pub command_line: String,
+    pub cwd: String,
+    /// `KEY=VALUE` pairs set on top of the launcher's own environment.
+    pub env: Vec<String>,
+}
+
+/// CPU scheduling priority for launched games: a nice level on Unix, a
+/// priority class on Windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum ProcessPriority {
+    Idle,
+    BelowNormal,
+    #[default]
+    Normal,
+    AboveNormal,
+    High,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum RunnerKind {
+    #[default]
+    Native,
+    SystemWine,
+    CustomWine,
+    Proton,
+}
+
+/// How a game's executable is run. `path` is the Wine binary (or build
+/// folder) for `CustomWine` and the Proton install folder for `Proton`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct RunnerConfig {
+    #[serde(default)]
+    pub kind: RunnerKind,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum WineArch {
+    Win32,
+    #[default]
+    Win64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct WinePrefix {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub arch: WineArch,
+    /// Wine binary used to manage the prefix; `None` uses the system `wine`.
+    #[serde(default)]
+    pub wine_path: Option<String>,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub dll_overrides: HashMap<String, String>,
+    pub created_at: String,
+    /// The folder was created by the launcher rather than adopted, so its
+    /// files may be deleted along with the prefix.
+    #[serde(default)]
+    pub created_by_launcher: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
@@ -135,7 +371,7 @@ pub struct VndbAuthInfo {
     pub username: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, specta::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct AppSettings {
     pub vndb_token: Option<String>,
     pub vndb_user_id: Option<String>,
@@ -148,6 +384,66 @@ pub struct AppSettings {
     pub discord_btn_vndb_profile: bool,
     #[serde(default)]
     pub discord_btn_github: bool,
+    #[serde(default)]
+    pub default_runner: RunnerConfig,
+    /// Windows wrapper template for Japanese locale launches, e.g.
+    /// `"C:\Tools\LE\LEProc.exe" -run "{exe}" {args}`.
+    #[serde(default)]
+    pub locale_emulator_command: Option<String>,
+    #[serde(default)]
+    pub hooks: HookCommands,
+    #[serde(default = "default_hook_timeout_secs")]
+    pub hook_timeout_secs: u64,
+    /// Watch running processes for library games started outside the launcher.
+    #[serde(default)]
+    pub auto_detect_games: bool,
+    #[serde(default)]
+    pub idle_source: IdleSourceKind,
+    /// Minutes without activity before a session counts as idle. Settings
+    /// saved with 0 before it was validated still turn idle detection off.
+    #[serde(default = "default_idle_threshold_mins")]
+    pub idle_threshold_mins: u64,
+    /// Local hour (0-23) at which a new day starts for daily play time, so
+    /// late-night sessions can count toward the previous day.
+    #[serde(default)]
+    pub day_start_hour: u8,
+    /// Commands every game is launched through, outermost first.
+    #[serde(default)]
+    pub wrappers: Vec<String>,
+    #[serde(default)]
+    pub priority: ProcessPriority,
+    /// Seconds between CPU and memory samples of running games; 0 turns
+    /// resource monitoring off.
+    #[serde(default = "default_resource_sample_secs")]
+    pub resource_sample_secs: u64,
+    #[serde(default)]
+    pub emulators: Vec<EmulatorConfig>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            vndb_token: None,
+            vndb_user_id: None,
+            blur_nsfw: false,
+            discord_rpc_enabled: false,
+            discord_btn_vndb_game: false,
+            discord_btn_vndb_profile: false,
+            discord_btn_github: false,
+            default_runner: RunnerConfig::default(),
+            locale_emulator_command: None,
+            hooks: HookCommands::default(),
+            hook_timeout_secs: default_hook_timeout_secs(),
+            auto_detect_games: false,
+            idle_source: IdleSourceKind::default(),
+            idle_threshold_mins: default_idle_threshold_mins(),
+            day_start_hour: 0,
+            wrappers: Vec::new(),
+            priority: ProcessPriority::default(),
+            resource_sample_secs: default_resource_sample_secs(),
+            emulators: Vec::new(),
+        }
+    }
 }
 
 fn default_discord_enabled() -> bool {
@@ -158,16 +454,507 @@ fn default_true() -> bool {
     true
 }
 
+fn default_hook_timeout_secs() -> u64 {
+    60
+}
+
+fn default_idle_threshold_mins() -> u64 {
+    10
+}
+
+fn default_resource_sample_secs() -> u64 {
+    5
+}
+
+/// Where idle time comes from: input inactivity, the game's CPU usage, or
+/// input when available with CPU usage as the fallback.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum IdleSourceKind {
+    #[default]
+    Auto,
+    Input,
+    Cpu,
+    Off,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ScanCandidate {
+    pub title: String,
+    pub path: String,
+    pub folder: String,
+    pub alternatives: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgressPayload {
+    pub root: String,
+    pub current_dir: String,
+    pub scanned_dirs: u64,
+    pub found: u64,
+    pub finished: bool,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct GameExitedPayload {
     pub game_id: String,
+    pub profile_id: Option<String>,
     pub play_minutes: u64,
+    pub excluded_minutes: u64,
+    pub active_secs: u64,
+    pub exit_code: Option<i32>,
+    pub exit_signal: Option<i32>,
+    pub crashed: bool,
+    pub force_stopped: bool,
+    /// Output of the game's last run, if it was captured.
+    pub log_path: Option<String>,
+}
+
+/// A running game as reported to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct RunningSession {
+    pub game_id: String,
+    pub profile_id: Option<String>,
+    pub title: String,
+    pub cover_url: Option<String>,
+    pub started_at: u64,
+    pub elapsed_secs: u64,
+}
+
+/// The process a session follows, kept so it can be found again after a
+/// launcher restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedProcess {
+    pub pid: u32,
+    /// Start time of `pid`, to tell it apart from a later process reusing the pid.
+    pub pid_started_at: u64,
+    pub session_tag: Option<String>,
+    pub hook_path: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum ExclusionReason {
+    Suspend,
+    Idle,
+}
+
+/// A stretch of a session, in unix seconds, that does not count as play time.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct ExcludedSpan {
+    pub start: u64,
+    pub end: u64,
+    pub reason: ExclusionReason,
+}
+
+impl ExcludedSpan {
+    pub fn secs(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// Total excluded time, counting overlapping spans (e.g. idle during a
+/// suspend) only once.
+pub fn excluded_secs(spans: &[ExcludedSpan]) -> u64 {
+    let mut ranges: Vec<(u64, u64)> = spans.iter().map(|s| (s.start, s.end)).collect();
+    ranges.sort_unstable();
+
+    let mut total = 0;
+    let mut covered_to = 0;
+    for (start, end) in ranges {
+        let start = start.max(covered_to);
+        if end > start {
+            total += end - start;
+            covered_to = end;
+        }
+    }
+    total
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum SessionKind {
+    #[default]
+    Tracked,
+    /// Play time recorded before the session log existed.
+    Legacy,
+    /// Entered by hand to correct tracked play time.
+    Manual,
+}
+
+/// One finished play session in `session_history.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct SessionRecord {
+    pub id: String,
+    pub game_id: String,
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default)]
+    pub kind: SessionKind,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub active_secs: u64,
+    #[serde(default)]
+    pub excluded: Vec<ExcludedSpan>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Signal that terminated the game, on Unix.
+    #[serde(default)]
+    pub exit_signal: Option<i32>,
+    /// The game exited with an error, was killed by a signal, or quit right
+    /// after launch.
+    #[serde(default)]
+    pub crashed: bool,
+    #[serde(default)]
+    pub resources: Option<SessionResources>,
+    /// Seconds east of UTC when the session started. Days are split in this
+    /// timezone, so a later timezone change doesn't move past sessions.
+    #[serde(default)]
+    pub utc_offset: i32,
+    /// Minutes the session added to each `YYYY-MM-DD` day of the daily
+    /// stats. Removing it takes back exactly these, even after
+    /// `day_start_hour` changed. Empty for records from before this was kept.
+    #[serde(default)]
+    pub daily: Vec<(String, u64)>,
+}
+
+impl SessionRecord {
+    /// A tracked session; active time is the wall-clock span minus the
+    /// excluded spans, cut off at `ended_at`.
+    pub fn tracked(
+        game_id: String,
+        profile_id: Option<String>,
+        started_at: u64,
+        ended_at: u64,
+        mut excluded: Vec<ExcludedSpan>,
+    ) -> Self {
+        excluded.retain_mut(|span| {
+            span.end = span.end.min(ended_at);
+            span.end > span.start
+        });
+        let active_secs = ended_at
+            .saturating_sub(started_at)
+            .saturating_sub(excluded_secs(&excluded));
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            game_id,
+            profile_id,
+            kind: SessionKind::Tracked,
+            started_at,
+            ended_at,
+            active_secs,
+            excluded,
+            exit_code: None,
+            exit_signal: None,
+            crashed: false,
+            resources: None,
+            utc_offset: local_offset(started_at),
+            daily: Vec::new(),
+        }
+    }
+
+    pub fn set_daily(&mut self, days: &[(NaiveDate, u64)]) {
+        self.daily = days
+            .iter()
+            .map(|(date, minutes)| (date.to_string(), *minutes))
+            .collect();
+    }
+
+    /// The stored per-day split; see [`SessionRecord::daily`].
+    pub fn credited_days(&self) -> Vec<(NaiveDate, u64)> {
+        self.daily
+            .iter()
+            .filter_map(|(date, minutes)| Some((date.parse().ok()?, *minutes)))
+            .collect()
+    }
+
+    /// Seconds played between `from` and `to`, leaving out excluded spans.
+    fn active_between(&self, from: u64, to: u64) -> u64 {
+        let excluded: Vec<ExcludedSpan> = self
+            .excluded
+            .iter()
+            .filter_map(|span| {
+                let start = span.start.max(from);
+                let end = span.end.min(to);
+                (end > start).then_some(ExcludedSpan {
+                    start,
+                    end,
+                    reason: span.reason,
+                })
+            })
+            .collect();
+        to.saturating_sub(from)
+            .saturating_sub(excluded_secs(&excluded))
+    }
+
+    /// Active seconds per day, for days starting at `day_start_hour` in the
+    /// session's own timezone.
+    pub fn daily_secs(&self, day_start_hour: u8) -> Vec<(NaiveDate, u64)> {
+        let shift = self.utc_offset as i64 - day_start_hour as i64 * 3600;
+        let mut days = Vec::new();
+        let mut from = self.started_at;
+        while from < self.ended_at {
+            let day = (from as i64 + shift).div_euclid(SECS_PER_DAY);
+            let next = ((day + 1) * SECS_PER_DAY - shift).max(0) as u64;
+            let to = next.min(self.ended_at);
+            let date = chrono::DateTime::from_timestamp(day * SECS_PER_DAY, 0)
+                .map(|t| t.date_naive())
+                .unwrap_or_default();
+            days.push((date, self.active_between(from, to)));
+            from = to;
+        }
+        days
+    }
+}
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Offset of the local timezone from UTC, in seconds, at unix time `at`.
+pub fn local_offset(at: u64) -> i32 {
+    chrono::Local
+        .timestamp_opt(at as i64, 0)
+        .earliest()
+        .map(|t| t.offset().local_minus_utc())
+        .unwrap_or_default()
+}
+
+/// A manual play time correction.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type")]
+pub enum PlaytimeChange {
+    SessionAdded {
+        session: SessionRecord,
+    },
+    SessionEdited {
+        before: Box<SessionRecord>,
+        after: Box<SessionRecord>,
+    },
+    SessionDeleted {
+        session: SessionRecord,
+    },
+    /// A day's total in the daily stats was set by hand, in minutes.
+    DailyAdjusted {
+        date: String,
+        before: u64,
+        after: u64,
+    },
+}
+
+/// One entry in `playtime_audit.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct PlaytimeAuditEntry {
+    pub id: String,
+    pub game_id: String,
+    /// Unix seconds.
+    pub at: u64,
+    pub reason: String,
+    pub change: PlaytimeChange,
+    /// The entry that undid this one, if it was reverted.
+    #[serde(default)]
+    pub reverted_by: Option<String>,
+}
+
+/// CPU and memory use of a game's process tree at one point in time.
+#[derive(Debug, Clone, Copy, Default, Serialize, specta::Type)]
+pub struct ResourceUsage {
+    /// Summed over all processes, in percent of one core.
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+/// Average and peak resource use over a session.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, specta::Type)]
+pub struct SessionResources {
+    pub samples: u64,
+    pub avg_cpu_percent: f32,
+    pub peak_cpu_percent: f32,
+    pub avg_memory_bytes: u64,
+    pub peak_memory_bytes: u64,
+}
+
+/// Resource samples collected while a session runs. The aggregates are
+/// checkpointed with the session so a recovered session keeps them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceStats {
+    #[serde(skip)]
+    pub current: Option<ResourceUsage>,
+    samples: u64,
+    cpu_total: f64,
+    memory_total: u128,
+    peak_cpu: f32,
+    peak_memory: u64,
+}
+
+impl ResourceStats {
+    pub fn add(&mut self, usage: ResourceUsage) {
+        self.current = Some(usage);
+        self.samples += 1;
+        self.cpu_total += usage.cpu_percent as f64;
+        self.memory_total += usage.memory_bytes as u128;
+        self.peak_cpu = self.peak_cpu.max(usage.cpu_percent);
+        self.peak_memory = self.peak_memory.max(usage.memory_bytes);
+    }
+
+    pub fn summary(&self) -> Option<SessionResources> {
+        if self.samples == 0 {
+            return None;
+        }
+        Some(SessionResources {
+            samples: self.samples,
+            avg_cpu_percent: (self.cpu_total / self.samples as f64) as f32,
+            peak_cpu_percent: self.peak_cpu,
+            avg_memory_bytes: (self.memory_total / self.samples as u128) as u64,
+            peak_memory_bytes: self.peak_memory,
+        })
+    }
+}
+
+/// A running session as persisted in `active_sessions.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveSession {
+    pub game_id: String,
+    pub profile_id: Option<String>,
+    pub started_at: u64,
+    pub checkpoint_at: u64,
+    #[serde(default)]
+    pub excluded: Vec<ExcludedSpan>,
+    pub process: TrackedProcess,
+    #[serde(default)]
+    pub resources: ResourceStats,
 }
 
 pub struct RunningGame {
     pub id: String,
+    pub profile_id: Option<String>,
     pub start_time: Instant,
+    pub started_at: u64,
     pub title: String,
     pub cover_url: Option<String>,
-    pub discord_start_timestamp: u64,
+    pub excluded: Vec<ExcludedSpan>,
+    /// Start of the idle segment in progress, if the player is away.
+    pub idle_since: Option<u64>,
+    pub process: TrackedProcess,
+    /// Set when the launcher is terminating the game on the user's request.
+    pub force_stopped: bool,
+    pub resources: ResourceStats,
+    /// The game writes to its latest log, which is then kept under the size
+    /// cap. Attached and recovered sessions leave an earlier run's log alone.
+    pub owns_log: bool,
+}
+
+impl RunningGame {
+    /// Excluded spans so far, including an idle segment still in progress.
+    pub fn excluded_at(&self, now: u64) -> Vec<ExcludedSpan> {
+        let mut spans = self.excluded.clone();
+        if let Some(start) = self.idle_since {
+            spans.push(ExcludedSpan {
+                start,
+                end: now.max(start),
+                reason: ExclusionReason::Idle,
+            });
+        }
+        spans
+    }
+
+    /// Seconds played so far, leaving out excluded spans.
+    pub fn active_secs(&self, now: u64) -> u64 {
+        now.saturating_sub(self.started_at)
+            .saturating_sub(excluded_secs(&self.excluded_at(now)))
+    }
+
+    pub fn to_record(&self, ended_at: u64) -> SessionRecord {
+        SessionRecord {
+            resources: self.resources.summary(),
+            ..SessionRecord::tracked(
+                self.id.clone(),
+                self.profile_id.clone(),
+                self.started_at,
+                ended_at,
+                self.excluded_at(ended_at),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: u64, end: u64, reason: ExclusionReason) -> ExcludedSpan {
+        ExcludedSpan { start, end, reason }
+    }
+
+    #[test]
+    fn overlapping_excluded_spans_count_once() {
+        let spans = [
+            span(200, 210, ExclusionReason::Idle),
+            span(0, 100, ExclusionReason::Suspend),
+            span(50, 150, ExclusionReason::Idle),
+            span(205, 208, ExclusionReason::Suspend),
+            span(60, 70, ExclusionReason::Idle),
+        ];
+        assert_eq!(excluded_secs(&spans), 160);
+    }
+
+    #[test]
+    fn adjacent_and_empty_spans() {
+        let spans = [
+            span(0, 10, ExclusionReason::Idle),
+            span(10, 20, ExclusionReason::Suspend),
+            span(30, 30, ExclusionReason::Idle),
+        ];
+        assert_eq!(excluded_secs(&spans), 20);
+        assert_eq!(excluded_secs(&[]), 0);
+    }
+
+    /// 2024-01-01 00:00 UTC.
+    const JAN_1: u64 = 1_704_067_200;
+    const HOUR: u64 = 3600;
+
+    fn session(started_at: u64, ended_at: u64, utc_offset: i32) -> SessionRecord {
+        let mut record =
+            SessionRecord::tracked("game".into(), None, started_at, ended_at, Vec::new());
+        record.utc_offset = utc_offset;
+        record
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn daily_secs_splits_at_midnight() {
+        let record = session(JAN_1 + 22 * HOUR, JAN_1 + 26 * HOUR, 0);
+        assert_eq!(
+            record.daily_secs(0),
+            vec![(date(1), 2 * HOUR), (date(2), 2 * HOUR)]
+        );
+    }
+
+    #[test]
+    fn daily_secs_follows_day_start_hour() {
+        let record = session(JAN_1 + 22 * HOUR, JAN_1 + 26 * HOUR, 0);
+        assert_eq!(record.daily_secs(4), vec![(date(1), 4 * HOUR)]);
+        // Before 23:00 it is still the 31st.
+        let dec_31 = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+        assert_eq!(
+            record.daily_secs(23),
+            vec![(dec_31, HOUR), (date(1), 3 * HOUR)]
+        );
+    }
+
+    #[test]
+    fn daily_secs_uses_the_session_timezone() {
+        // 07:00 to 11:00 on the 2nd in UTC+9.
+        let record = session(JAN_1 + 22 * HOUR, JAN_1 + 26 * HOUR, 9 * 3600);
+        assert_eq!(record.daily_secs(0), vec![(date(2), 4 * HOUR)]);
+    }
+
+    #[test]
+    fn daily_secs_leaves_out_excluded_time() {
+        let mut record = session(JAN_1 + 22 * HOUR, JAN_1 + 26 * HOUR, 0);
+        record.excluded = vec![span(
+            JAN_1 + 23 * HOUR + 1800,
+            JAN_1 + 24 * HOUR + 1800,
+            ExclusionReason::Idle,
+        )];
+        assert_eq!(record.daily_secs(0), vec![(date(1), 5400), (date(2), 5400)]);
+    }
 }
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+use tauri::{AppHandle, Manager};
+
+use crate::models::TrackedProcess;
+use crate::process::{self, ProcessTree};
+use crate::state::AppState;
+
+/// How often the interval setting is re-read, so a change applies without
+/// waiting out the previous interval.
+const SLEEP_SLICE: Duration = Duration::from_secs(1);
+
+/// Background thread that samples CPU and memory use of every running
+/// game's process tree at the `resource_sample_secs` interval.
+pub fn spawn(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut sys = System::new();
+        let mut trees: HashMap<String, (Instant, u32, ProcessTree)> = HashMap::new();
+        let mut last_sample = Instant::now();
+        loop {
+            thread::sleep(SLEEP_SLICE);
+            let state = app_handle.state::<AppState>();
+            let interval = state.settings.lock().resource_sample_secs;
+            if interval == 0 {
+                trees.clear();
+                continue;
+            }
+            if last_sample.elapsed() < Duration::from_secs(interval) {
+                continue;
+            }
+            last_sample = Instant::now();
+
+            let sessions: Vec<(String, Instant, TrackedProcess)> = state
+                .running_games
+                .lock()
+                .values()
+                .map(|r| (r.id.clone(), r.start_time, r.process.clone()))
+                .collect();
+            // Sessions can be pointed at a new root process, e.g. once a
+            // store client starts the game.
+            trees.retain(|id, (start_time, pid, _)| {
+                sessions.iter().any(|(running, started, tracked)| {
+                    running == id && started == start_time && tracked.pid == *pid
+                })
+            });
+
+            for (id, start_time, tracked) in sessions {
+                if !trees.contains_key(&id) {
+                    let Some(pid) = process::find_session_process(&tracked) else {
+                        continue;
+                    };
+                    let tree = ProcessTree::new(pid, tracked.session_tag.as_deref());
+                    // CPU usage needs a previous sample to compare against.
+                    let pids: Vec<u32> = tree.pids().collect();
+                    process::resource_usage(&mut sys, &pids);
+                    trees.insert(id.clone(), (start_time, tracked.pid, tree));
+                    continue;
+                }
+                let Some((_, _, tree)) = trees.get_mut(&id) else {
+                    continue;
+                };
+                tree.refresh();
+                let pids: Vec<u32> = tree.pids().collect();
+                let usage = process::resource_usage(&mut sys, &pids);
+
+                if let Some(session) = state
+                    .running_games
+                    .lock()
+                    .get_mut(&id)
+                    .filter(|r| r.start_time == start_time)
+                {
+                    session.resources.add(usage);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,320 @@
+use chrono::NaiveDate;
+
+use crate::database::{
+    load_daily_playtime, record_daily_playtime, remove_daily_playtime, save_games,
+    save_playtime_audit, save_session_history, set_daily_playtime,
+};
+use crate::discord;
+use crate::error::{AppError, AppResult};
+use crate::models::{local_offset, PlaytimeAuditEntry, PlaytimeChange, SessionKind, SessionRecord};
+use crate::session;
+use crate::state::AppState;
+
+fn validate_reason(reason: &str) -> AppResult<String> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::Validation(
+            "A reason is required for play time changes".into(),
+        ));
+    }
+    Ok(reason.to_string())
+}
+
+fn validate_date(date: &str) -> AppResult<()> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| AppError::Validation(format!("Invalid date: {}", date)))
+}
+
+fn ensure_game(state: &AppState, game_id: &str) -> AppResult<()> {
+    if state.games.lock().iter().any(|g| g.id == game_id) {
+        Ok(())
+    } else {
+        Err(AppError::NotFound("Game not found".into()))
+    }
+}
+
+fn find_session(state: &AppState, session_id: &str) -> AppResult<SessionRecord> {
+    state
+        .session_history
+        .lock()
+        .iter()
+        .find(|r| r.id == session_id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound("Session not found".into()))
+}
+
+/// Logs a manual session of `active_secs` starting at `started_at`.
+pub fn add_session(
+    state: &AppState,
+    game_id: String,
+    started_at: u64,
+    active_secs: u64,
+    reason: &str,
+) -> AppResult<PlaytimeAuditEntry> {
+    let reason = validate_reason(reason)?;
+    ensure_game(state, &game_id)?;
+    let session = SessionRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        game_id: game_id.clone(),
+        profile_id: None,
+        kind: SessionKind::Manual,
+        started_at,
+        ended_at: started_at.saturating_add(active_secs),
+        active_secs,
+        excluded: Vec::new(),
+        exit_code: None,
+        exit_signal: None,
+        crashed: false,
+        resources: None,
+        utc_offset: local_offset(started_at),
+        daily: Vec::new(),
+    };
+    commit(
+        state,
+        game_id,
+        reason,
+        PlaytimeChange::SessionAdded { session },
+    )
+}
+
+/// Moves a session to `started_at` and sets how long it counted for. The
+/// session's excluded spans are dropped since its length is now explicit.
+pub fn edit_session(
+    state: &AppState,
+    session_id: &str,
+    started_at: u64,
+    active_secs: u64,
+    reason: &str,
+) -> AppResult<PlaytimeAuditEntry> {
+    let reason = validate_reason(reason)?;
+    let before = find_session(state, session_id)?;
+    let after = SessionRecord {
+        started_at,
+        ended_at: started_at.saturating_add(active_secs),
+        active_secs,
+        excluded: Vec::new(),
+        ..before.clone()
+    };
+    commit(
+        state,
+        before.game_id.clone(),
+        reason,
+        PlaytimeChange::SessionEdited {
+            before: Box::new(before),
+            after: Box::new(after),
+        },
+    )
+}
+
+pub fn delete_session(
+    state: &AppState,
+    session_id: &str,
+    reason: &str,
+) -> AppResult<PlaytimeAuditEntry> {
+    let reason = validate_reason(reason)?;
+    let session = find_session(state, session_id)?;
+    commit(
+        state,
+        session.game_id.clone(),
+        reason,
+        PlaytimeChange::SessionDeleted { session },
+    )
+}
+
+/// Sets a game's total in the daily stats for `date` (`YYYY-MM-DD`) without
+/// touching its sessions.
+pub fn adjust_daily(
+    state: &AppState,
+    game_id: String,
+    date: String,
+    minutes: u64,
+    reason: &str,
+) -> AppResult<PlaytimeAuditEntry> {
+    let reason = validate_reason(reason)?;
+    validate_date(&date)?;
+    ensure_game(state, &game_id)?;
+    let before = load_daily_playtime()
+        .games
+        .get(&game_id)
+        .and_then(|days| days.get(&date))
+        .copied()
+        .unwrap_or(0);
+    commit(
+        state,
+        game_id,
+        reason,
+        PlaytimeChange::DailyAdjusted {
+            date,
+            before,
+            after: minutes,
+        },
+    )
+}
+
+/// Undoes an earlier change by applying its inverse, which is logged as a
+/// change of its own.
+pub fn revert(
+    state: &AppState,
+    audit_id: &str,
+    reason: Option<&str>,
+) -> AppResult<PlaytimeAuditEntry> {
+    let mut audit = state.playtime_audit.lock();
+    let original = audit
+        .iter()
+        .find(|e| e.id == audit_id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound("Audit entry not found".into()))?;
+    if original.reverted_by.is_some() {
+        return Err(AppError::Validation(
+            "This change has already been reverted".into(),
+        ));
+    }
+    let reason = match reason {
+        Some(reason) => validate_reason(reason)?,
+        None => format!("Revert: {}", original.reason),
+    };
+
+    record(
+        state,
+        &mut audit,
+        original.game_id,
+        reason,
+        inverse(&original.change),
+        Some(audit_id),
+    )
+}
+
+fn inverse(change: &PlaytimeChange) -> PlaytimeChange {
+    match change.clone() {
+        PlaytimeChange::SessionAdded { session } => PlaytimeChange::SessionDeleted { session },
+        PlaytimeChange::SessionEdited { before, after } => PlaytimeChange::SessionEdited {
+            before: after,
+            after: before,
+        },
+        PlaytimeChange::SessionDeleted { session } => PlaytimeChange::SessionAdded { session },
+        PlaytimeChange::DailyAdjusted {
+            date,
+            before,
+            after,
+        } => PlaytimeChange::DailyAdjusted {
+            date,
+            before: after,
+            after: before,
+        },
+    }
+}
+
+/// Applies `change` and appends it to the audit log.
+fn commit(
+    state: &AppState,
+    game_id: String,
+    reason: String,
+    change: PlaytimeChange,
+) -> AppResult<PlaytimeAuditEntry> {
+    let mut audit = state.playtime_audit.lock();
+    record(state, &mut audit, game_id, reason, change, None)
+}
+
+/// Applies `change` and logs it, marking the entry it `reverts` as undone.
+/// If the log can't be saved the change is rolled back, so play time never
+/// changes without a record of why.
+fn record(
+    state: &AppState,
+    audit: &mut Vec<PlaytimeAuditEntry>,
+    game_id: String,
+    reason: String,
+    change: PlaytimeChange,
+    reverts: Option<&str>,
+) -> AppResult<PlaytimeAuditEntry> {
+    apply(state, &game_id, &change)?;
+
+    let entry = PlaytimeAuditEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        game_id,
+        at: discord::get_unix_timestamp(),
+        reason,
+        change,
+        reverted_by: None,
+    };
+    let mut updated = audit.clone();
+    if let Some(original) = updated.iter_mut().find(|e| Some(e.id.as_str()) == reverts) {
+        original.reverted_by = Some(entry.id.clone());
+    }
+    updated.push(entry.clone());
+
+    if let Err(e) = save_playtime_audit(&updated) {
+        if let Err(undo) = apply(state, &entry.game_id, &inverse(&entry.change)) {
+            log::error!("Failed to roll back unlogged play time change: {}", undo);
+        }
+        return Err(e);
+    }
+    *audit = updated;
+    Ok(entry)
+}
+
+fn apply(state: &AppState, game_id: &str, change: &PlaytimeChange) -> AppResult<()> {
+    let (removed, added) = match change {
+        PlaytimeChange::SessionAdded { session } => (None, Some(session)),
+        PlaytimeChange::SessionEdited { before, after } => (Some(&**before), Some(&**after)),
+        PlaytimeChange::SessionDeleted { session } => (Some(session), None),
+        PlaytimeChange::DailyAdjusted {
+            date,
+            before,
+            after,
+        } => {
+            // Applied as a delta so reverting an old adjustment keeps any
+            // play recorded on that day since.
+            let current = load_daily_playtime()
+                .games
+                .get(game_id)
+                .and_then(|days| days.get(date))
+                .copied()
+                .unwrap_or(0);
+            let minutes = current.saturating_add_signed(*after as i64 - *before as i64);
+            set_daily_playtime(game_id, date, minutes)?;
+            return Ok(());
+        }
+    };
+
+    // A removed session takes back the minutes it was credited with; an
+    // added one is split with the current day start and keeps its split.
+    let day_start_hour = state.settings.lock().day_start_hour;
+    let (lost, gained, total) = {
+        let mut history = state.session_history.lock();
+        let mut lost = Vec::new();
+        if let Some(removed) = removed {
+            let index = history
+                .iter()
+                .position(|r| r.id == removed.id)
+                .ok_or_else(|| AppError::NotFound("Session not found".into()))?;
+            lost = session::credited_minutes(&history, index, day_start_hour);
+            history.remove(index);
+        }
+        let mut gained = Vec::new();
+        if let Some(added) = added {
+            if history.iter().any(|r| r.id == added.id) {
+                return Err(AppError::Validation("Session already exists".into()));
+            }
+            let mut added = added.clone();
+            gained = session::daily_minutes(
+                session::total_secs(&history, game_id),
+                &added.daily_secs(day_start_hour),
+            );
+            added.set_daily(&gained);
+            history.push(added);
+        }
+        save_session_history(&history)?;
+        (lost, gained, session::total_secs(&history, game_id))
+    };
+
+    remove_daily_playtime(game_id, &lost);
+    record_daily_playtime(game_id, &gained);
+
+    let mut games = state.games.lock();
+    if let Some(game) = games.iter_mut().find(|g| g.id == game_id) {
+        game.play_time = total / 60;
+        save_games(&games)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,512 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{
+    Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System, UpdateKind,
+};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{GameMetadata, ResourceUsage, TrackedProcess};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const TREE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Exported to every launched game; descendants inherit it, which lets us
+/// find them even after their parent has exited and they were reparented.
+pub const SESSION_ENV: &str = "ALKA_SESSION";
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    if cfg!(windows) {
+        a.to_string_lossy()
+            .eq_ignore_ascii_case(&b.to_string_lossy())
+    } else {
+        a == b
+    }
+}
+
+fn refresh(sys: &mut System) {
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_environ(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+fn is_alive(process: &Process) -> bool {
+    !matches!(
+        process.status(),
+        ProcessStatus::Zombie | ProcessStatus::Dead
+    )
+}
+
+fn is_executable_running(sys: &System, exe: &Path) -> bool {
+    sys.processes()
+        .values()
+        .any(|p| p.exe().map(|e| same_path(e, exe)).unwrap_or(false))
+}
+
+/// File name of a path that may use either separator, since Wine processes
+/// report Windows paths in their command line.
+fn file_name(path: &OsStr) -> String {
+    let path = path.to_string_lossy();
+    path.rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn matches_executable_name(process: &Process, name: &str) -> bool {
+    let candidates = [
+        Some(process.name().to_string_lossy().to_string()),
+        process.exe().map(|e| file_name(e.as_os_str())),
+        process.cmd().first().map(|c| file_name(c)),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .any(|c| c.eq_ignore_ascii_case(name))
+}
+
+fn is_name_running(sys: &System, name: &str) -> bool {
+    sys.processes()
+        .values()
+        .any(|p| is_alive(p) && matches_executable_name(p, name))
+}
+
+/// Trims a "track executable" override and makes sure it is a bare file name.
+pub fn validate_executable_name(name: Option<&str>) -> AppResult<Option<String>> {
+    let name = match name.map(str::trim) {
+        Some(n) if !n.is_empty() => n,
+        _ => return Ok(None),
+    };
+    if name.contains(['/', '\\']) {
+        return Err(AppError::Validation(format!(
+            "Expected an executable name, not a path: {}",
+            name
+        )));
+    }
+    Ok(Some(name.to_string()))
+}
+
+/// The processes belonging to one launch: the spawned child, everything it
+/// starts, and anything carrying the session's `ALKA_SESSION` tag.
+pub struct ProcessTree {
+    sys: System,
+    tag: Option<String>,
+    started_at: u64,
+    pids: HashSet<Pid>,
+}
+
+impl ProcessTree {
+    pub fn new(root: u32, tag: Option<&str>) -> Self {
+        let root = Pid::from_u32(root);
+        let mut sys = System::new();
+        refresh(&mut sys);
+        let started_at = sys
+            .process(root)
+            .map(|p| p.start_time())
+            .unwrap_or_else(|| unix_now().saturating_sub(1));
+        Self {
+            sys,
+            tag: tag.map(|t| format!("{}={}", SESSION_ENV, t)),
+            started_at,
+            pids: HashSet::from([root]),
+        }
+    }
+
+    fn belongs(&self, process: &Process) -> bool {
+        let tagged = self
+            .tag
+            .as_deref()
+            .map(|tag| process.environ().iter().any(|e| e.as_os_str() == tag))
+            .unwrap_or(false);
+        // Pids get reused, so only processes newer than the root are
+        // adopted by parentage; the session tag is unique on its own.
+        let child_of_tree = process.start_time() >= self.started_at
+            && process
+                .parent()
+                .map(|parent| self.pids.contains(&parent))
+                .unwrap_or(false);
+        tagged || child_of_tree
+    }
+
+    /// Picks up new descendants, drops exited processes and returns whether
+    /// anything in the tree is still running.
+    pub fn refresh(&mut self) -> bool {
+        refresh(&mut self.sys);
+
+        loop {
+            let adopted: Vec<Pid> = self
+                .sys
+                .processes()
+                .iter()
+                .filter(|(pid, p)| !self.pids.contains(pid) && is_alive(p) && self.belongs(p))
+                .map(|(pid, _)| *pid)
+                .collect();
+            if adopted.is_empty() {
+                break;
+            }
+            self.pids.extend(adopted);
+        }
+
+        let processes = self.sys.processes();
+        self.pids
+            .retain(|pid| processes.get(pid).map(is_alive).unwrap_or(false));
+        !self.pids.is_empty()
+    }
+
+    /// Whether a process of the tree matches the executable `name`.
+    fn runs(&self, name: &str) -> bool {
+        self.pids
+            .iter()
+            .filter_map(|pid| self.sys.process(*pid))
+            .any(|p| matches_executable_name(p, name))
+    }
+
+    pub fn pids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.pids.iter().map(|pid| pid.as_u32())
+    }
+}
+
+/// Waits for the spawned child and every process it started. Bootstrappers
+/// that spawn the real game and exit right away keep the session open until
+/// the game itself is gone. Also returns whether a process matching `track`
+/// was part of the tree, in which case it has already been waited for.
+pub fn wait_for_tree(
+    child: &mut Child,
+    tag: &str,
+    track: Option<&str>,
+) -> io::Result<(ExitStatus, bool)> {
+    let mut tree = ProcessTree::new(child.id(), Some(tag));
+    let mut status = None;
+    let mut seen = false;
+
+    loop {
+        if status.is_none() {
+            status = child.try_wait()?;
+        }
+        let alive = tree.refresh();
+        seen = seen || track.is_some_and(|name| tree.runs(name));
+        if let Some(status) = status {
+            if !alive {
+                return Ok((status, seen));
+            }
+        }
+        thread::sleep(TREE_POLL_INTERVAL);
+    }
+}
+
+/// Waits for a process we did not spawn, and everything it starts, to exit.
+pub fn wait_for_process_tree(pid: u32, tag: Option<&str>) {
+    let mut tree = ProcessTree::new(pid, tag);
+    while tree.refresh() {
+        thread::sleep(TREE_POLL_INTERVAL);
+    }
+}
+
+/// Combined CPU and memory use of `pids`. CPU usage is measured since the
+/// previous call with the same `sys`, so the first sample reads as zero.
+pub fn resource_usage(sys: &mut System, pids: &[u32]) -> ResourceUsage {
+    let pids: Vec<Pid> = pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+    pids.iter()
+        .filter_map(|pid| sys.process(*pid))
+        .fold(ResourceUsage::default(), |usage, p| ResourceUsage {
+            cpu_percent: usage.cpu_percent + p.cpu_usage(),
+            memory_bytes: usage.memory_bytes + p.memory(),
+        })
+}
+
+/// Signal that terminated a process, on platforms that have them.
+pub fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
+/// Unix start time of a live process, or `None` if there is no live process
+/// with that pid.
+pub fn start_time(pid: u32) -> Option<u64> {
+    let mut sys = System::new();
+    refresh(&mut sys);
+    sys.process(Pid::from_u32(pid))
+        .filter(|p| is_alive(p))
+        .map(|p| p.start_time())
+}
+
+/// Finds the game process of a session recorded before a restart: the
+/// original pid if it still belongs to the same process, otherwise any
+/// process still carrying the session tag.
+pub fn find_session_process(tracked: &TrackedProcess) -> Option<u32> {
+    let mut sys = System::new();
+    refresh(&mut sys);
+
+    let original = sys
+        .process(Pid::from_u32(tracked.pid))
+        .filter(|p| is_alive(p) && p.start_time() == tracked.pid_started_at);
+    if original.is_some() {
+        return Some(tracked.pid);
+    }
+
+    let tag = format!("{}={}", SESSION_ENV, tracked.session_tag.as_deref()?);
+    sys.processes()
+        .iter()
+        .find(|(_, p)| is_alive(p) && p.environ().iter().any(|e| e.as_os_str() == tag.as_str()))
+        .map(|(pid, _)| pid.as_u32())
+}
+
+/// Windows paths as seen in a Wine process command line (`Z:\home\...`)
+/// turned back into the Unix path they refer to.
+fn unix_path_from_wine(path: &str) -> Option<PathBuf> {
+    let rest = path
+        .strip_prefix("Z:")
+        .or_else(|| path.strip_prefix("z:"))?;
+    Some(PathBuf::from(rest.replace('\\', "/")))
+}
+
+fn runs_path(process: &Process, path: &Path) -> bool {
+    if process.exe().map(|e| same_path(e, path)).unwrap_or(false) {
+        return true;
+    }
+    let Some(arg0) = process.cmd().first() else {
+        return false;
+    };
+    let arg0 = arg0.to_string_lossy();
+    same_path(Path::new(arg0.as_ref()), path)
+        || unix_path_from_wine(&arg0)
+            .map(|p| same_path(&p, path))
+            .unwrap_or(false)
+}
+
+fn launched_by_us(process: &Process) -> bool {
+    let prefix = format!("{}=", SESSION_ENV);
+    process
+        .environ()
+        .iter()
+        .any(|e| e.to_string_lossy().starts_with(&prefix))
+}
+
+/// A library game found running without having been launched through us.
+pub struct DetectedGame {
+    pub game_id: String,
+    pub profile_id: Option<String>,
+    pub pid: u32,
+    pub started_at: u64,
+}
+
+/// Looks through running processes for one of `games`, matching the
+/// executable path of the game or one of its profiles, or its
+/// "track executable" name. Processes in `skip`, given as pid and start
+/// time so recycled pids are not skipped, are ignored.
+pub fn find_external_game(
+    sys: &mut System,
+    games: &[GameMetadata],
+    skip: &HashSet<(u32, u64)>,
+) -> Option<DetectedGame> {
+    refresh(sys);
+    for (pid, process) in sys.processes() {
+        if skip.contains(&(pid.as_u32(), process.start_time()))
+            || !is_alive(process)
+            || launched_by_us(process)
+        {
+            continue;
+        }
+        for game in games {
+            let profile = game
+                .profiles
+                .iter()
+                .find(|p| runs_path(process, Path::new(&p.path)));
+            let matched = profile.is_some()
+                || runs_path(process, Path::new(&game.path))
+                || game
+                    .track_executable
+                    .as_deref()
+                    .map(|name| matches_executable_name(process, name))
+                    .unwrap_or(false);
+            if matched {
+                return Some(DetectedGame {
+                    game_id: game.id.clone(),
+                    profile_id: profile.map(|p| p.id.clone()),
+                    pid: pid.as_u32(),
+                    started_at: process.start_time(),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Whether `pid` is still the live process that started at `started_at`,
+/// as of the last refresh of `sys`.
+pub fn is_same_process(sys: &System, pid: u32, started_at: u64) -> bool {
+    sys.process(Pid::from_u32(pid))
+        .is_some_and(|p| is_alive(p) && p.start_time() == started_at)
+}
+
+/// Returns whether the process showed up at all.
+fn wait_for_process<F>(description: &str, appear_timeout: Duration, running: F) -> bool
+where
+    F: Fn(&System) -> bool,
+{
+    let mut sys = System::new();
+    let started = Instant::now();
+
+    loop {
+        refresh(&mut sys);
+        if running(&sys) {
+            break;
+        }
+        if started.elapsed() >= appear_timeout {
+            log::warn!("{} never started after its launcher exited", description);
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    while running(&sys) {
+        thread::sleep(POLL_INTERVAL);
+        refresh(&mut sys);
+    }
+    true
+}
+
+/// Waits for a game that was started by a wrapper which exits right away
+/// (e.g. Locale Emulator): first for the executable to show up, then for
+/// every process running it to exit. Returns whether it showed up.
+pub fn wait_for_detached_executable(exe: &Path, appear_timeout: Duration) -> bool {
+    wait_for_process(&format!("{:?}", exe), appear_timeout, |sys| {
+        is_executable_running(sys, exe)
+    })
+}
+
+/// Waits up to `appear_timeout` for a process matching `name` and returns
+/// its pid.
+pub fn wait_for_executable_pid(name: &str, appear_timeout: Duration) -> Option<u32> {
+    let mut sys = System::new();
+    let started = Instant::now();
+    loop {
+        refresh(&mut sys);
+        let found = sys
+            .processes()
+            .iter()
+            .find(|(_, p)| is_alive(p) && matches_executable_name(p, name));
+        if let Some((pid, _)) = found {
+            return Some(pid.as_u32());
+        }
+        if started.elapsed() >= appear_timeout {
+            log::warn!("{} never started after its launcher exited", name);
+            return None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Same as [`wait_for_detached_executable`] but matches any process by
+/// executable name, for launchers that detach the game completely.
+pub fn wait_for_executable_name(name: &str, appear_timeout: Duration) -> bool {
+    wait_for_process(name, appear_timeout, |sys| is_name_running(sys, name))
+}
+
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Every live process of a session: its process tree when there is one.
+/// Otherwise (a launcher detached the game and the tag was lost) anything
+/// running the game's executable or matching its "track executable" name.
+fn session_pids(
+    tree: Option<&mut ProcessTree>,
+    sys: &mut System,
+    exe: &Path,
+    name: Option<&str>,
+) -> HashSet<Pid> {
+    refresh(sys);
+    if let Some(tree) = tree {
+        tree.refresh();
+        return tree.pids().map(Pid::from_u32).collect();
+    }
+    sys.processes()
+        .iter()
+        .filter(|(_, p)| {
+            is_alive(p)
+                && (runs_path(p, exe) || name.is_some_and(|n| matches_executable_name(p, n)))
+        })
+        .map(|(pid, _)| *pid)
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn request_exit(sys: &System, pid: Pid) {
+    if let Some(process) = sys.process(pid) {
+        process.kill_with(sysinfo::Signal::Term);
+    }
+}
+
+/// Windows has no SIGTERM; `taskkill` without `/F` asks the windows of the
+/// process to close instead.
+#[cfg(windows)]
+fn request_exit(_sys: &System, pid: Pid) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.as_u32().to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
+/// Asks every process of a session to exit, then kills whatever is still
+/// running after `grace`. Returns whether anything had to be killed.
+pub fn terminate_session(tracked: &TrackedProcess, name: Option<&str>, grace: Duration) -> bool {
+    let mut tree = find_session_process(tracked)
+        .map(|pid| ProcessTree::new(pid, tracked.session_tag.as_deref()));
+    if tree.is_none() {
+        log::info!("Session process is gone, stopping processes by executable instead");
+    }
+    let mut sys = System::new();
+    let exe = Path::new(&tracked.hook_path);
+
+    let pids = session_pids(tree.as_mut(), &mut sys, exe, name);
+    for pid in &pids {
+        request_exit(&sys, *pid);
+    }
+
+    let started = Instant::now();
+    loop {
+        let pids = session_pids(tree.as_mut(), &mut sys, exe, name);
+        if pids.is_empty() {
+            return false;
+        }
+        if started.elapsed() >= grace {
+            log::warn!("Killing {} processes that ignored the exit request", pids.len());
+            for pid in &pids {
+                if let Some(process) = sys.process(*pid) {
+                    process.kill();
+                }
+            }
+            return true;
+        }
+        thread::sleep(STOP_POLL_INTERVAL);
+    }
+}
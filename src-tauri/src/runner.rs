@@ -0,0 +1,235 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::{AppError, AppResult};
+use crate::launcher::LaunchPlan;
+use crate::models::{RunnerConfig, RunnerKind};
+
+/// The Wine installation a game runs under, used to wait for every process
+/// in its prefix once the loader we spawned has exited.
+#[derive(Debug, Clone)]
+pub struct WineContext {
+    pub wineserver: PathBuf,
+    pub prefix: Option<PathBuf>,
+    /// No other game runs in the prefix, so waiting for its wineserver only
+    /// waits for this game. Shared prefixes such as `~/.wine` rely on the
+    /// session-tagged process tree instead.
+    pub dedicated: bool,
+    /// Proton's `STEAM_COMPAT_DATA_PATH`, which has to exist before it runs.
+    pub compat_data: Option<PathBuf>,
+}
+
+impl WineContext {
+    /// Creates the directories the runner expects. Called right before
+    /// spawning, so planning a launch never touches the disk.
+    pub fn prepare(&self) -> AppResult<()> {
+        if let Some(ref compat_data) = self.compat_data {
+            std::fs::create_dir_all(compat_data)?;
+        }
+        Ok(())
+    }
+
+    /// Blocks until the prefix's wineserver exits, i.e. until every Windows
+    /// process started inside the prefix (including detached children) is gone.
+    pub fn wait_for_prefix(&self) {
+        let mut cmd = Command::new(&self.wineserver);
+        cmd.arg("-w").stdout(Stdio::null()).stderr(Stdio::null());
+        if let Some(ref prefix) = self.prefix {
+            cmd.env("WINEPREFIX", prefix);
+        }
+        match cmd.status() {
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to wait for wineserver {:?}: {}", self.wineserver, e),
+        }
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// A custom Wine build may be given as the `wine` binary or its install root.
+fn custom_wine_binary(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.join("bin").join("wine")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn proton_wineserver(proton_dir: &Path) -> PathBuf {
+    let files = proton_dir.join("files").join("bin").join("wineserver");
+    if files.exists() {
+        files
+    } else {
+        proton_dir.join("dist").join("bin").join("wineserver")
+    }
+}
+
+pub fn validate_runner(runner: &RunnerConfig) -> AppResult<RunnerConfig> {
+    let path = non_empty(&runner.path).map(str::to_string);
+    let prefix = non_empty(&runner.prefix).map(str::to_string);
+
+    if runner.kind != RunnerKind::Native && cfg!(windows) {
+        return Err(AppError::Validation(
+            "Wine and Proton runners are not available on Windows".into(),
+        ));
+    }
+
+    match runner.kind {
+        RunnerKind::Native | RunnerKind::SystemWine => {}
+        RunnerKind::CustomWine => {
+            let path = path.as_deref().ok_or_else(|| {
+                AppError::Validation("Custom Wine runner requires a Wine path".into())
+            })?;
+            if !custom_wine_binary(Path::new(path)).is_file() {
+                return Err(AppError::Validation(format!(
+                    "Wine binary not found: {}",
+                    path
+                )));
+            }
+        }
+        RunnerKind::Proton => {
+            let path = path.as_deref().ok_or_else(|| {
+                AppError::Validation("Proton runner requires a Proton directory".into())
+            })?;
+            if !Path::new(path).join("proton").is_file() {
+                return Err(AppError::Validation(format!(
+                    "No proton script found in {}",
+                    path
+                )));
+            }
+            if prefix.is_none() {
+                return Err(AppError::Validation(
+                    "Proton runner requires a prefix path".into(),
+                ));
+            }
+        }
+    }
+
+    if let Some(ref prefix) = prefix {
+        let prefix_path = Path::new(prefix);
+        if prefix_path.exists() && !prefix_path.is_dir() {
+            return Err(AppError::Validation(format!(
+                "Prefix path is not a folder: {}",
+                prefix
+            )));
+        }
+    }
+
+    Ok(RunnerConfig {
+        kind: runner.kind.clone(),
+        path,
+        prefix,
+    })
+}
+
+/// Rewrites a native launch plan so it runs through the configured runner.
+pub fn apply_runner(
+    plan: LaunchPlan,
+    runner: &RunnerConfig,
+) -> AppResult<(LaunchPlan, Option<WineContext>)> {
+    let runner = validate_runner(runner)?;
+
+    let is_windows_exe = plan
+        .program
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("exe"))
+        .unwrap_or(false);
+
+    if runner.kind == RunnerKind::Native {
+        if is_windows_exe && !cfg!(windows) {
+            return Err(AppError::Validation(
+                "Windows executables need a Wine or Proton runner on this platform".into(),
+            ));
+        }
+        return Ok((plan, None));
+    }
+
+    let prefix = runner.prefix.as_ref().map(PathBuf::from);
+
+    let mut args = vec![plan.program.to_string_lossy().to_string()];
+    args.extend(plan.args.iter().cloned());
+    let mut env = plan.env.clone();
+
+    let (program, wine) = match runner.kind {
+        RunnerKind::Native | RunnerKind::SystemWine => (
+            PathBuf::from("wine"),
+            WineContext {
+                wineserver: PathBuf::from("wineserver"),
+                prefix: prefix.clone(),
+                dedicated: false,
+                compat_data: None,
+            },
+        ),
+        RunnerKind::CustomWine => {
+            let wine = custom_wine_binary(Path::new(runner.path.as_deref().unwrap_or_default()));
+            let wineserver = wine.with_file_name("wineserver");
+            (
+                wine,
+                WineContext {
+                    wineserver,
+                    prefix: prefix.clone(),
+                    dedicated: false,
+                    compat_data: None,
+                },
+            )
+        }
+        RunnerKind::Proton => {
+            let proton_dir = PathBuf::from(runner.path.as_deref().unwrap_or_default());
+            args.insert(0, "run".into());
+            (
+                proton_dir.join("proton"),
+                WineContext {
+                    wineserver: proton_wineserver(&proton_dir),
+                    prefix: prefix.as_ref().map(|p| p.join("pfx")),
+                    dedicated: false,
+                    compat_data: prefix.clone(),
+                },
+            )
+        }
+    };
+
+    let mut set_default_env = |key: &str, value: String| {
+        if !env.iter().any(|(k, _)| k == key) {
+            env.push((key.to_string(), value));
+        }
+    };
+
+    match runner.kind {
+        RunnerKind::Proton => {
+            if let Some(ref prefix) = prefix {
+                set_default_env(
+                    "STEAM_COMPAT_DATA_PATH",
+                    prefix.to_string_lossy().to_string(),
+                );
+            }
+            let steam_dir = dirs::home_dir()
+                .unwrap_or_default()
+                .join(".steam")
+                .join("steam");
+            set_default_env(
+                "STEAM_COMPAT_CLIENT_INSTALL_PATH",
+                steam_dir.to_string_lossy().to_string(),
+            );
+        }
+        _ => {
+            if let Some(ref prefix) = prefix {
+                set_default_env("WINEPREFIX", prefix.to_string_lossy().to_string());
+            }
+        }
+    }
+    env.sort();
+
+    Ok((
+        LaunchPlan {
+            program,
+            args,
+            cwd: plan.cwd,
+            env,
+            priority: plan.priority,
+        },
+        Some(wine),
+    ))
+}
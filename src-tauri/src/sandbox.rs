@@ -0,0 +1,258 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult};
+use crate::launcher::LaunchPlan;
+use crate::models::{SandboxConfig, SandboxTool};
+use crate::runner::WineContext;
+
+/// Env vars naming directories the runner writes to.
+const WRITABLE_ENV: [&str; 2] = ["WINEPREFIX", "STEAM_COMPAT_DATA_PATH"];
+/// Env vars naming directories the runner only reads from.
+const READABLE_ENV: [&str; 2] = ["STEAM_COMPAT_CLIENT_INSTALL_PATH", "XAUTHORITY"];
+const X11_SOCKETS: &str = "/tmp/.X11-unix";
+
+/// Trims allowed paths, drops empty ones and requires the rest to be
+/// absolute.
+pub fn validate_sandbox(sandbox: &SandboxConfig) -> AppResult<SandboxConfig> {
+    let mut allowed_paths = Vec::new();
+    for path in sandbox.allowed_paths.iter().map(|p| p.trim()) {
+        if path.is_empty() {
+            continue;
+        }
+        if !Path::new(path).is_absolute() {
+            return Err(AppError::Validation(format!(
+                "Sandbox paths must be absolute: {}",
+                path
+            )));
+        }
+        allowed_paths.push(path.to_string());
+    }
+    Ok(SandboxConfig {
+        allowed_paths,
+        ..sandbox.clone()
+    })
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+fn resolve_tool(tool: SandboxTool) -> AppResult<SandboxTool> {
+    let installed = |tool: SandboxTool| match tool {
+        SandboxTool::Bubblewrap => on_path("bwrap"),
+        SandboxTool::Firejail => on_path("firejail"),
+        SandboxTool::Auto => false,
+    };
+    match tool {
+        SandboxTool::Auto => [SandboxTool::Bubblewrap, SandboxTool::Firejail]
+            .into_iter()
+            .find(|t| installed(*t))
+            .ok_or_else(|| {
+                AppError::ProcessLaunch("Sandboxing needs bubblewrap or firejail".into())
+            }),
+        tool if installed(tool) => Ok(tool),
+        tool => Err(AppError::ProcessLaunch(format!(
+            "{:?} is not installed",
+            tool
+        ))),
+    }
+}
+
+/// Directory of the program the plan runs, widened to the install root for
+/// `.../bin/wine` style layouts.
+fn program_root(program: &Path) -> Option<PathBuf> {
+    if !program.is_absolute() {
+        return None;
+    }
+    let dir = program.parent()?;
+    match dir.file_name() {
+        Some(name) if name == "bin" => dir.parent().map(Path::to_path_buf),
+        _ => Some(dir.to_path_buf()),
+    }
+}
+
+fn wine_prefix(wine: &WineContext, home: &Path) -> PathBuf {
+    wine.prefix.clone().unwrap_or_else(|| home.join(".wine"))
+}
+
+/// Creates the Wine prefix directory a sandboxed launch binds, so Wine sets
+/// the prefix up inside the bind mount instead of in the throwaway home.
+/// Called right before spawning, never for launch previews.
+pub fn prepare(sandbox: &SandboxConfig, wine: Option<&WineContext>) -> AppResult<()> {
+    if let (true, Some(wine)) = (sandbox.enabled, wine) {
+        let home = dirs::home_dir().unwrap_or_default();
+        std::fs::create_dir_all(wine_prefix(wine, &home))?;
+    }
+    Ok(())
+}
+
+/// Hides the runtime directory behind an empty tmpfs and brings back only
+/// the Wayland and PulseAudio sockets. The rest of it, the D-Bus session bus
+/// and the systemd user manager in particular, would let a game ask the
+/// session for the network and home directory the sandbox took away.
+fn runtime_args(runtime_dir: &Path, wayland_display: Option<&str>) -> Vec<String> {
+    let path = |p: &Path| p.to_string_lossy().to_string();
+    let mut args = vec![
+        "--tmpfs".into(),
+        path(runtime_dir),
+        "--unsetenv".into(),
+        "DBUS_SESSION_BUS_ADDRESS".into(),
+    ];
+    let sockets = wayland_display
+        .filter(|display| !display.is_empty())
+        .map(|display| runtime_dir.join(display))
+        .into_iter()
+        .chain([runtime_dir.join("pulse").join("native")]);
+    for socket in sockets {
+        // Read-only mounts still let clients connect to a socket.
+        args.extend(["--ro-bind-try".into(), path(&socket), path(&socket)]);
+    }
+    args
+}
+
+/// Runs the plan inside bubblewrap or firejail with the home directory
+/// hidden, except for the game, its Wine prefix and `allowed_paths`, and
+/// without network unless the config allows it. The sandbox keeps the host
+/// pid namespace and environment so the session can still be tracked.
+pub fn apply_sandbox(
+    plan: LaunchPlan,
+    sandbox: &SandboxConfig,
+    target: &Path,
+    wine: Option<&WineContext>,
+) -> AppResult<LaunchPlan> {
+    if !cfg!(target_os = "linux") {
+        return Err(AppError::Validation(
+            "Sandboxed launches are only supported on Linux".into(),
+        ));
+    }
+    let tool = resolve_tool(sandbox.tool)?;
+    let home = dirs::home_dir().unwrap_or_default();
+    let env_path = |key: &str| {
+        plan.env
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| PathBuf::from(v))
+            .or_else(|| std::env::var_os(key).map(PathBuf::from))
+    };
+
+    let mut writable: Vec<PathBuf> = vec![plan.cwd.clone()];
+    writable.extend(target.parent().map(Path::to_path_buf));
+    writable.extend(WRITABLE_ENV.iter().filter_map(|key| env_path(key)));
+    writable.extend(sandbox.allowed_paths.iter().map(PathBuf::from));
+    writable.retain(|p| p.exists());
+    // Bound even if it doesn't exist yet; `prepare` creates it at launch.
+    writable.extend(wine.map(|wine| wine_prefix(wine, &home)));
+    writable.sort();
+    writable.dedup();
+
+    let mut readable: Vec<PathBuf> = READABLE_ENV
+        .iter()
+        .filter_map(|key| env_path(key))
+        .collect();
+    readable.extend(program_root(&plan.program));
+    readable.push(home.join(".Xauthority"));
+    readable.push(home.join(".config").join("pulse"));
+    readable.retain(|p| p.exists());
+
+    let mut args: Vec<String> = Vec::new();
+    let path = |p: &Path| p.to_string_lossy().to_string();
+    let program = match tool {
+        SandboxTool::Firejail => {
+            args.extend(["--quiet".into(), "--noprofile".into()]);
+            if !sandbox.allow_network {
+                args.push("--net=none".into());
+            }
+            if !writable
+                .iter()
+                .chain(&readable)
+                .any(|p| p.starts_with(&home))
+            {
+                args.push("--private".into());
+            }
+            for dir in &readable {
+                args.push(format!("--whitelist={}", path(dir)));
+                args.push(format!("--read-only={}", path(dir)));
+            }
+            for dir in &writable {
+                args.push(format!("--whitelist={}", path(dir)));
+                args.push(format!("--read-write={}", path(dir)));
+            }
+            "firejail"
+        }
+        _ => {
+            args.extend(["--ro-bind", "/", "/", "--dev-bind", "/dev", "/dev"].map(String::from));
+            args.extend(["--tmpfs".into(), path(&home)]);
+            // Wine and most engines need a writable /tmp; the X11 sockets
+            // live there too.
+            args.extend(
+                ["--tmpfs", "/tmp", "--ro-bind-try", X11_SOCKETS, X11_SOCKETS].map(String::from),
+            );
+            if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+                let wayland_display = std::env::var("WAYLAND_DISPLAY").ok();
+                args.extend(runtime_args(
+                    Path::new(&runtime_dir),
+                    wayland_display.as_deref(),
+                ));
+            }
+            for dir in &readable {
+                args.extend(["--ro-bind-try".into(), path(dir), path(dir)]);
+            }
+            for dir in &writable {
+                args.extend(["--bind-try".into(), path(dir), path(dir)]);
+            }
+            if !sandbox.allow_network {
+                args.push("--unshare-net".into());
+            }
+            // No --die-with-parent: games keep running when the launcher
+            // exits and their session is recovered on the next start.
+            args.extend(["--chdir".into(), path(&plan.cwd)]);
+            "bwrap"
+        }
+    };
+    args.push("--".into());
+    args.push(path(&plan.program));
+    args.extend(plan.args);
+
+    Ok(LaunchPlan {
+        program: PathBuf::from(program),
+        args,
+        ..plan
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_dir_is_not_bound() {
+        let runtime_dir = Path::new("/run/user/1000");
+        let args = runtime_args(runtime_dir, Some("wayland-0"));
+
+        let binds: Vec<&[String]> = args.windows(3).filter(|w| w[0].contains("bind")).collect();
+        assert!(binds
+            .iter()
+            .all(|w| w[1] != "/run/user/1000" && w[2] != "/run/user/1000"));
+        assert!(!args
+            .iter()
+            .any(|a| a.ends_with("/bus") || a.contains("systemd")));
+        assert_eq!(args[..2], ["--tmpfs", "/run/user/1000"]);
+        assert!(binds
+            .iter()
+            .any(|w| w[0] == "--ro-bind-try" && w[1] == "/run/user/1000/wayland-0"));
+        assert!(binds
+            .iter()
+            .any(|w| w[0] == "--ro-bind-try" && w[1] == "/run/user/1000/pulse/native"));
+    }
+
+    #[test]
+    fn absolute_wayland_display_is_kept() {
+        let args = runtime_args(Path::new("/run/user/1000"), Some("/tmp/wayland-1"));
+        assert!(args.iter().any(|a| a == "/tmp/wayland-1"));
+        assert!(!runtime_args(Path::new("/run/user/1000"), Some(""))
+            .iter()
+            .any(|a| a == "/run/user/1000/"));
+    }
+}
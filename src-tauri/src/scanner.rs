@@ -0,0 +1,340 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::{ScanCandidate, ScanProgressPayload};
+
+const MAX_SCAN_DEPTH: usize = 8;
+const PROGRESS_EVERY_DIRS: u64 = 25;
+
+const EXECUTABLE_EXTENSIONS: &[&str] = &["exe"];
+
+// Lowercased file stems of executables that are never the game itself
+const SKIPPED_EXECUTABLES: &[&str] = &[
+    "uninstall",
+    "uninst",
+    "setup",
+    "install",
+    "installer",
+    "config",
+    "configure",
+    "setting",
+    "settings",
+    "crashhandler",
+    "crashreport",
+    "crashreporter",
+    "dxsetup",
+    "dxwebsetup",
+    "oalinst",
+    "redist",
+    "updater",
+    "notification_helper",
+    "設定",
+];
+
+// Tool families whose stems carry a number or architecture suffix, e.g.
+// `unins000` or `vc_redist.x64`
+const SKIPPED_EXECUTABLE_PREFIXES: &[&str] = &[
+    "unins",
+    "unitycrashhandler",
+    "vcredist",
+    "vc_redist",
+    "directx",
+    "dotnetfx",
+    "ndp4",
+    "physx",
+    "ue4prereqsetup",
+];
+
+// Folders that only ever contain tooling or runtime installers
+const SKIPPED_DIRS: &[&str] = &[
+    "_commonredist",
+    "redist",
+    "redistributable",
+    "directx",
+    "vcredist",
+    "__macosx",
+    "$recycle.bin",
+    "system volume information",
+];
+
+pub fn normalize_path(path: &Path) -> String {
+    let resolved = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let s = resolved.to_string_lossy().to_string();
+    if cfg!(windows) {
+        s.to_lowercase()
+    } else {
+        s
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| EXECUTABLE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn is_skipped_executable(stem: &str) -> bool {
+    let lower = stem.to_lowercase();
+    SKIPPED_EXECUTABLES.contains(&lower.as_str())
+        || SKIPPED_EXECUTABLE_PREFIXES.iter().any(|prefix| {
+            lower
+                .strip_prefix(prefix)
+                .is_some_and(|rest| !rest.starts_with(char::is_alphabetic))
+        })
+}
+
+fn is_skipped_dir(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.starts_with('.') || SKIPPED_DIRS.contains(&lower.as_str())
+}
+
+fn simplify(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Picks the most likely game executable out of a folder's executables.
+/// Executables named after the folder win; otherwise the largest one does.
+fn pick_executable(folder_name: &str, executables: &[(PathBuf, u64)]) -> Option<PathBuf> {
+    let folder = simplify(folder_name);
+
+    executables
+        .iter()
+        .max_by_key(|(path, size)| {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(simplify)
+                .unwrap_or_default();
+            let name_score = if stem.is_empty() || folder.is_empty() {
+                0
+            } else if stem == folder {
+                2
+            } else if folder.contains(&stem) || stem.contains(&folder) {
+                1
+            } else {
+                0
+            };
+            (name_score, *size)
+        })
+        .map(|(path, _)| path.clone())
+}
+
+/// Turns a folder name like `[RJ123456] Some_Title (v1.02)` into `Some Title`.
+pub fn title_from_folder(name: &str) -> String {
+    let mut title = String::new();
+    let mut depth = 0usize;
+    for c in name.chars() {
+        match c {
+            '[' | '(' | '【' | '（' => depth += 1,
+            ']' | ')' | '】' | '）' => depth = depth.saturating_sub(1),
+            '_' if depth == 0 => title.push(' '),
+            _ if depth == 0 => title.push(c),
+            _ => {}
+        }
+    }
+
+    let cleaned = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if cleaned.is_empty() {
+        name.trim().to_string()
+    } else {
+        cleaned
+    }
+}
+
+struct Scan<'a, F: FnMut(ScanProgressPayload)> {
+    root: String,
+    known_paths: &'a HashSet<String>,
+    seen: HashSet<String>,
+    candidates: Vec<ScanCandidate>,
+    scanned_dirs: u64,
+    on_progress: F,
+}
+
+impl<F: FnMut(ScanProgressPayload)> Scan<'_, F> {
+    fn report(&mut self, current_dir: &Path) {
+        (self.on_progress)(ScanProgressPayload {
+            root: self.root.clone(),
+            current_dir: current_dir.to_string_lossy().to_string(),
+            scanned_dirs: self.scanned_dirs,
+            found: self.candidates.len() as u64,
+            finished: false,
+        });
+    }
+
+    fn visit(&mut self, dir: &Path, depth: usize) {
+        self.scanned_dirs += 1;
+        if self.scanned_dirs % PROGRESS_EVERY_DIRS == 0 {
+            self.report(dir);
+        }
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("scan_library: Cannot read {:?}: {}", dir, e);
+                return;
+            }
+        };
+
+        let mut executables = Vec::new();
+        let mut subdirs = Vec::new();
+        let mut owned_by_library = false;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+
+            if file_type.is_dir() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !is_skipped_dir(&name) {
+                    subdirs.push(path);
+                }
+            } else if file_type.is_file() && is_executable(&path) {
+                if self.known_paths.contains(&normalize_path(&path)) {
+                    owned_by_library = true;
+                    continue;
+                }
+                let stem = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default();
+                if is_skipped_executable(stem) {
+                    continue;
+                }
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                executables.push((path, size));
+            }
+        }
+
+        // A folder that already holds a library game or a new candidate is a
+        // game folder; its subfolders are tools, patches and redistributables.
+        if owned_by_library {
+            return;
+        }
+
+        let folder_name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if let Some(exe) = pick_executable(&folder_name, &executables) {
+            let key = normalize_path(&exe);
+            if self.seen.insert(key) {
+                let alternatives = executables
+                    .iter()
+                    .filter(|(p, _)| *p != exe)
+                    .map(|(p, _)| p.to_string_lossy().to_string())
+                    .collect();
+                self.candidates.push(ScanCandidate {
+                    title: title_from_folder(&folder_name),
+                    path: exe.to_string_lossy().to_string(),
+                    folder: dir.to_string_lossy().to_string(),
+                    alternatives,
+                });
+                self.report(dir);
+            }
+            return;
+        }
+
+        if depth >= MAX_SCAN_DEPTH {
+            return;
+        }
+
+        subdirs.sort();
+        for sub in subdirs {
+            self.visit(&sub, depth + 1);
+        }
+    }
+}
+
+/// Walks every root and proposes one executable per game folder, skipping
+/// executables whose normalized path is already in `known_paths`.
+pub fn scan_roots<F>(
+    roots: &[PathBuf],
+    known_paths: &HashSet<String>,
+    mut on_progress: F,
+) -> Vec<ScanCandidate>
+where
+    F: FnMut(ScanProgressPayload),
+{
+    let mut candidates = Vec::new();
+    let mut seen = HashSet::new();
+    let mut scanned_dirs = 0;
+
+    for root in roots {
+        let mut scan = Scan {
+            root: root.to_string_lossy().to_string(),
+            known_paths,
+            seen,
+            candidates,
+            scanned_dirs,
+            on_progress: &mut on_progress,
+        };
+        scan.visit(root, 0);
+        log::info!(
+            "scan_library: {:?} done, {} candidates so far",
+            root,
+            scan.candidates.len()
+        );
+
+        seen = scan.seen;
+        candidates = scan.candidates;
+        scanned_dirs = scan.scanned_dirs;
+    }
+
+    on_progress(ScanProgressPayload {
+        root: String::new(),
+        current_dir: String::new(),
+        scanned_dirs,
+        found: candidates.len() as u64,
+        finished: true,
+    });
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_installers_and_tools() {
+        for stem in [
+            "unins000",
+            "Uninstall",
+            "setup",
+            "Config",
+            "vc_redist.x64",
+            "vcredist_x86",
+            "UnityCrashHandler64",
+            "DXSETUP",
+            "ndp472-kb4054530-x86-x64-allos-enu",
+            "UE4PrereqSetup_x64",
+            "設定",
+        ] {
+            assert!(is_skipped_executable(stem), "{} should be skipped", stem);
+        }
+    }
+
+    #[test]
+    fn keeps_games_named_like_tools() {
+        for stem in [
+            "Reinstall",
+            "SetupMaster",
+            "Configurator",
+            "Game",
+            "uninstalled_love",
+            "PhysxLoader",
+            "settings_of_summer",
+        ] {
+            assert!(!is_skipped_executable(stem), "{} should be kept", stem);
+        }
+    }
+}
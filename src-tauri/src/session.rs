@@ -0,0 +1,829 @@
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::process::ExitStatus;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::task;
+
+use crate::clock::ClockReading;
+use crate::database::{
+    get_current_timestamp, load_active_sessions, load_session_history, record_daily_playtime,
+    save_active_sessions, save_games, save_session_history,
+};
+use crate::discord;
+use crate::error::{AppError, AppResult};
+use crate::game_log;
+use crate::hooks::{self, HookContext};
+use crate::idle::{self, IdleSource};
+use crate::models::{
+    excluded_secs, ActiveSession, ExcludedSpan, ExclusionReason, GameExitedPayload, GameMetadata,
+    IdleSourceKind, ResourceStats, RunningGame, SessionKind, SessionRecord, TrackedProcess,
+};
+use crate::process::{self, ProcessTree};
+use crate::state::AppState;
+
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+/// Suspends shorter than this are not worth an excluded span.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(30);
+/// Wall clock adjustments smaller than this are left to NTP slewing.
+const WALL_JUMP_THRESHOLD_SECS: i64 = 2;
+/// Games that exit sooner than this after launch count as crashed, even with
+/// a clean exit code: engines missing a runtime or codec often quit with 0
+/// before showing a window, while players rarely close a game this fast.
+/// Force-stopped sessions never count as crashed.
+const QUICK_EXIT: Duration = Duration::from_secs(10);
+const GITHUB_URL: &str = "https://github.com/betadyne/AlkaLauncher";
+
+/// Holds a game's running slot from before its pre-launch hooks until its
+/// session has begun, so two launches can't both spawn it. The slot is
+/// released when the reservation is dropped, on success or error.
+pub struct LaunchReservation<'a> {
+    state: &'a AppState,
+    game_id: String,
+}
+
+impl Drop for LaunchReservation<'_> {
+    fn drop(&mut self) {
+        self.state.launching.lock().remove(&self.game_id);
+    }
+}
+
+pub fn reserve<'a>(state: &'a AppState, game: &GameMetadata) -> AppResult<LaunchReservation<'a>> {
+    let running = state.running_games.lock();
+    let mut launching = state.launching.lock();
+    if running.contains_key(&game.id) || !launching.insert(game.id.clone()) {
+        return Err(AppError::Validation(format!(
+            "{} is already running",
+            game.title
+        )));
+    }
+    Ok(LaunchReservation {
+        state,
+        game_id: game.id.clone(),
+    })
+}
+
+/// Marks `game` as running and publishes its Discord activity. A
+/// `started_at` in the past backdates the session for games that were
+/// already running when we picked them up.
+pub fn begin(
+    state: &AppState,
+    game: &GameMetadata,
+    profile_id: Option<String>,
+    process: TrackedProcess,
+    started_at: u64,
+    excluded: Vec<ExcludedSpan>,
+    resources: ResourceStats,
+) -> Instant {
+    let age = Duration::from_secs(discord::get_unix_timestamp().saturating_sub(started_at));
+    let start_time = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+    let discord_start = started_at + excluded_secs(&excluded);
+
+    state.running_games.lock().insert(
+        game.id.clone(),
+        RunningGame {
+            id: game.id.clone(),
+            profile_id,
+            start_time,
+            started_at,
+            title: game.title.clone(),
+            cover_url: game.cover_url.clone(),
+            excluded,
+            idle_since: None,
+            process,
+            force_stopped: false,
+            resources,
+            owns_log: false,
+        },
+    );
+    checkpoint(state);
+
+    set_discord_activity(state, game, discord_start);
+    start_time
+}
+
+/// Points a running session at the process that turned out to be the game,
+/// e.g. once a store client has started it. The session restarts from now,
+/// since waiting for the client to start the game isn't play time.
+pub fn retarget(state: &AppState, game_id: &str, start_time: Instant, pid: u32) {
+    {
+        let mut running = state.running_games.lock();
+        let Some(session) = running
+            .get_mut(game_id)
+            .filter(|r| r.start_time == start_time)
+        else {
+            return;
+        };
+        session.started_at = discord::get_unix_timestamp();
+        session.excluded.clear();
+        session.idle_since = None;
+        session.process.pid = pid;
+        session.process.pid_started_at = process::start_time(pid).unwrap_or_default();
+        session.process.session_tag = None;
+    }
+    checkpoint(state);
+    refresh_discord_activity(state);
+}
+
+/// Drops a session without recording it, for launches whose game never
+/// showed up.
+pub fn discard(state: &AppState, game_id: &str, start_time: Instant) {
+    let removed = {
+        let mut running = state.running_games.lock();
+        match running.get(game_id) {
+            Some(r) if r.start_time == start_time => running.remove(game_id),
+            _ => None,
+        }
+    };
+    if removed.is_some() {
+        checkpoint(state);
+    }
+}
+
+/// Writes every running session to disk with the current time as its
+/// checkpoint, so a crash loses at most one checkpoint interval.
+pub fn checkpoint(state: &AppState) {
+    let now = discord::get_unix_timestamp();
+    let sessions: Vec<ActiveSession> = state
+        .running_games
+        .lock()
+        .values()
+        .map(|r| ActiveSession {
+            game_id: r.id.clone(),
+            profile_id: r.profile_id.clone(),
+            started_at: r.started_at,
+            checkpoint_at: now,
+            excluded: r.excluded_at(now),
+            process: r.process.clone(),
+            resources: r.resources.clone(),
+        })
+        .collect();
+    if let Err(e) = save_active_sessions(&sessions) {
+        log::warn!("Failed to save active sessions: {}", e);
+    }
+}
+
+pub fn total_secs(history: &[SessionRecord], game_id: &str) -> u64 {
+    history
+        .iter()
+        .filter(|r| r.game_id == game_id)
+        .map(|r| r.active_secs)
+        .sum()
+}
+
+/// Loads the session log, seeding it with a `Legacy` record for every game
+/// that has play time from before the log existed.
+pub fn load_history(games: &[GameMetadata]) -> Vec<SessionRecord> {
+    let mut history = load_session_history();
+    let mut migrated = false;
+
+    for game in games {
+        if game.play_time == 0 || history.iter().any(|r| r.game_id == game.id) {
+            continue;
+        }
+        let last_played = game
+            .last_played
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+        let at = last_played
+            .map(|t| t.timestamp().max(0) as u64)
+            .unwrap_or_default();
+        history.push(SessionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            game_id: game.id.clone(),
+            profile_id: None,
+            kind: SessionKind::Legacy,
+            started_at: at,
+            ended_at: at,
+            active_secs: game.play_time * 60,
+            excluded: Vec::new(),
+            exit_code: None,
+            exit_signal: None,
+            crashed: false,
+            resources: None,
+            utc_offset: last_played
+                .map(|t| t.offset().local_minus_utc())
+                .unwrap_or_default(),
+            daily: Vec::new(),
+        });
+        migrated = true;
+    }
+
+    if migrated {
+        if let Err(e) = save_session_history(&history) {
+            log::error!("Failed to save migrated session history: {}", e);
+        }
+    }
+    history
+}
+
+/// Appends a finished session to the log and re-derives the game's
+/// `play_time` from it, so sub-minute remainders carry over between
+/// sessions. Returns how many minutes `play_time` grew by.
+pub fn record_session(state: &AppState, mut record: SessionRecord) -> AppResult<u64> {
+    let game_id = record.game_id.clone();
+    let day_start_hour = state.settings.lock().day_start_hour;
+    let (days, total) = {
+        let mut history = state.session_history.lock();
+        let days = daily_minutes(
+            total_secs(&history, &game_id),
+            &record.daily_secs(day_start_hour),
+        );
+        record.set_daily(&days);
+        history.push(record);
+        save_session_history(&history)?;
+        (days, total_secs(&history, &game_id))
+    };
+
+    let added = {
+        let mut games = state.games.lock();
+        let Some(game) = games.iter_mut().find(|g| g.id == game_id) else {
+            return Ok(0);
+        };
+        let minutes = total / 60;
+        let added = minutes.saturating_sub(game.play_time);
+        game.play_time = minutes;
+        game.last_played = Some(get_current_timestamp());
+        save_games(&games)?;
+        added
+    };
+
+    record_daily_playtime(&game_id, &days);
+    Ok(added)
+}
+
+/// Turns per-day seconds into per-day minutes, carrying the remainder of
+/// earlier play so the days add up to the growth of the game's play time.
+pub fn daily_minutes(before_secs: u64, days: &[(NaiveDate, u64)]) -> Vec<(NaiveDate, u64)> {
+    let mut played = before_secs;
+    days.iter()
+        .map(|(date, secs)| {
+            let minutes = (played + secs) / 60 - played / 60;
+            played += secs;
+            (*date, minutes)
+        })
+        .collect()
+}
+
+/// The minutes `history[index]` added to the daily stats. Records from
+/// before the split was stored are replayed from the session log, which
+/// assumes `day_start_hour` hasn't changed since. `Legacy` records have no
+/// real dates, so they added nothing.
+pub fn credited_minutes(
+    history: &[SessionRecord],
+    index: usize,
+    day_start_hour: u8,
+) -> Vec<(NaiveDate, u64)> {
+    let record = &history[index];
+    if !record.daily.is_empty() {
+        return record.credited_days();
+    }
+    if record.kind == SessionKind::Legacy {
+        return Vec::new();
+    }
+    let played = total_secs(&history[..index], &record.game_id);
+    daily_minutes(played, &record.daily_secs(day_start_hour))
+}
+
+fn set_discord_activity(state: &AppState, game: &GameMetadata, discord_start: u64) {
+    let settings = state.settings.lock();
+    if !settings.discord_rpc_enabled {
+        return;
+    }
+
+    // Get developer name from VN cache if available
+    let developer = game.vndb_id.as_ref().and_then(|vndb_id| {
+        state.vn_mem_cache.lock().get(vndb_id).and_then(|vn| {
+            vn.developers.as_ref().and_then(|devs| {
+                devs.first().map(|d| d.name.clone())
+            })
+        })
+    });
+
+    let vndb_game_url = game.vndb_id.as_ref().map(|id| format!("https://vndb.org/{}", id));
+    let vndb_profile_url = settings.vndb_user_id.as_ref().map(|id| format!("https://vndb.org/{}", id));
+
+    let mut buttons: Vec<(&str, String)> = Vec::new();
+
+    if settings.discord_btn_vndb_game {
+        if let Some(ref url) = vndb_game_url {
+            buttons.push(("View on VNDB", url.clone()));
+        }
+    }
+    if settings.discord_btn_vndb_profile && buttons.len() < 2 {
+        if let Some(ref url) = vndb_profile_url {
+            buttons.push(("My VNDB Profile", url.clone()));
+        }
+    }
+    if settings.discord_btn_github && buttons.len() < 2 {
+        buttons.push(("GitHub", GITHUB_URL.to_string()));
+    }
+
+    let button_refs: Vec<(&str, &str)> = buttons
+        .iter()
+        .map(|(label, url)| (*label, url.as_str()))
+        .collect();
+
+    log::info!("Discord RPC buttons: {:?}", button_refs.iter().map(|(l, _)| *l).collect::<Vec<_>>());
+
+    let _ = state.discord_rpc.set_activity(
+        &game.title,
+        game.cover_url.as_deref(),
+        developer.as_deref(),
+        button_refs,
+        discord_start,
+    );
+}
+
+/// Shows the most recently started game that is still running on Discord,
+/// or clears the activity once nothing is left.
+pub fn refresh_discord_activity(state: &AppState) {
+    let latest = state
+        .running_games
+        .lock()
+        .values()
+        .max_by_key(|r| r.start_time)
+        .map(|r| (r.id.clone(), r.started_at + excluded_secs(&r.excluded)));
+
+    let game = latest.and_then(|(id, discord_start)| {
+        let games = state.games.lock();
+        games
+            .iter()
+            .find(|g| g.id == id)
+            .map(|g| (g.clone(), discord_start))
+    });
+    match game {
+        Some((game, discord_start)) => set_discord_activity(state, &game, discord_start),
+        None => {
+            let _ = state.discord_rpc.clear_activity();
+        }
+    }
+}
+
+/// Records a finished session, notifies the frontend and runs the post-exit
+/// hooks. Sessions already closed with `stop_tracking` are not counted twice.
+/// `ended_at` is when the game's last process exited.
+pub async fn end(
+    app_handle: &AppHandle,
+    game_id: String,
+    profile_id: Option<String>,
+    start_time: Instant,
+    hook_path: String,
+    status: Option<ExitStatus>,
+    ended_at: u64,
+) {
+    let state = app_handle.state::<AppState>();
+
+    let finished = {
+        let mut running = state.running_games.lock();
+        let is_current = running
+            .get(&game_id)
+            .map(|r| r.start_time == start_time)
+            .unwrap_or(false);
+        if is_current {
+            running.remove(&game_id)
+        } else {
+            None
+        }
+    };
+    let force_stopped = finished.as_ref().is_some_and(|r| r.force_stopped);
+    let record = finished.map(|r| {
+        let mut record = r.to_record(ended_at);
+        if let Some(status) = status {
+            record.exit_code = status.code();
+            record.exit_signal = process::exit_signal(&status);
+            record.crashed = !r.force_stopped
+                && (!status.success()
+                    || ended_at.saturating_sub(r.started_at) < QUICK_EXIT.as_secs());
+        }
+        record
+    });
+    let (exit_code, exit_signal, crashed) = record
+        .as_ref()
+        .map(|r| (r.exit_code, r.exit_signal, r.crashed))
+        .unwrap_or_default();
+    if crashed {
+        log::warn!(
+            "{} crashed (exit code {:?}, signal {:?})",
+            game_id,
+            exit_code,
+            exit_signal
+        );
+    }
+    let log_path = Some(game_log::latest_path(&game_id))
+        .filter(|path| status.is_some() && path.exists())
+        .map(|path| path.to_string_lossy().to_string());
+    let (minutes, excluded_minutes, active_secs) = match record {
+        Some(record) => {
+            let excluded = excluded_secs(&record.excluded) / 60;
+            let active_secs = record.active_secs;
+            checkpoint(&state);
+            let minutes = record_session(&state, record).unwrap_or_else(|e| {
+                log::error!("Failed to record session for {}: {}", game_id, e);
+                0
+            });
+            (minutes, excluded, active_secs)
+        }
+        None => (0, 0, 0),
+    };
+    refresh_discord_activity(&state);
+
+    let _ = app_handle.emit(
+        "game-exited",
+        GameExitedPayload {
+            game_id: game_id.clone(),
+            profile_id,
+            play_minutes: minutes,
+            excluded_minutes,
+            active_secs,
+            exit_code,
+            exit_signal,
+            crashed,
+            force_stopped,
+            log_path,
+        },
+    );
+
+    let Some((title, game_hooks)) = state
+        .games
+        .lock()
+        .iter()
+        .find(|g| g.id == game_id)
+        .map(|g| (g.title.clone(), g.hooks.clone()))
+    else {
+        return;
+    };
+    let (global_hooks, hook_timeout) = {
+        let settings = state.settings.lock();
+        (
+            settings.hooks.clone(),
+            Duration::from_secs(settings.hook_timeout_secs.max(1)),
+        )
+    };
+    let ctx = HookContext {
+        game_id,
+        title,
+        path: hook_path,
+        session_minutes: Some(minutes),
+    };
+    let _ = task::spawn_blocking(move || {
+        hooks::run_post_exit(&global_hooks, &game_hooks, &ctx, hook_timeout)
+    })
+    .await;
+}
+
+/// Follows an already running process tree and ends the session once it exits.
+fn follow(
+    app_handle: &AppHandle,
+    game: &GameMetadata,
+    profile_id: Option<String>,
+    process: TrackedProcess,
+    started_at: u64,
+    excluded: Vec<ExcludedSpan>,
+    resources: ResourceStats,
+) {
+    let state = app_handle.state::<AppState>();
+    let pid = process.pid;
+    let tag = process.session_tag.clone();
+    let hook_path = process.hook_path.clone();
+    let start_time = begin(
+        &state,
+        game,
+        profile_id.clone(),
+        process,
+        started_at,
+        excluded,
+        resources,
+    );
+
+    let app_handle = app_handle.clone();
+    let game_id = game.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ =
+            task::spawn_blocking(move || process::wait_for_process_tree(pid, tag.as_deref()))
+                .await;
+        let ended_at = discord::get_unix_timestamp();
+        end(
+            &app_handle,
+            game_id,
+            profile_id,
+            start_time,
+            hook_path,
+            None,
+            ended_at,
+        )
+        .await;
+    });
+}
+
+/// Starts a session for a game process we did not spawn ourselves, e.g. one
+/// started from a file manager, and ends it once its process tree exits.
+pub fn track_external(
+    app_handle: &AppHandle,
+    game: &GameMetadata,
+    profile_id: Option<String>,
+    pid: u32,
+) -> bool {
+    let Some(pid_started_at) = process::start_time(pid) else {
+        return false;
+    };
+    log::info!("Tracking {} from already running process {}", game.title, pid);
+    let hook_path = profile_id
+        .as_deref()
+        .and_then(|id| game.profiles.iter().find(|p| p.id == id))
+        .map(|p| p.path.clone())
+        .unwrap_or_else(|| game.path.clone());
+    let process = TrackedProcess {
+        pid,
+        pid_started_at,
+        session_tag: None,
+        hook_path,
+    };
+    follow(
+        app_handle,
+        game,
+        profile_id,
+        process,
+        pid_started_at,
+        Vec::new(),
+        ResourceStats::default(),
+    );
+    true
+}
+
+/// Picks up sessions left behind by a previous run that crashed or was
+/// killed: ones whose game is still running are tracked again, the rest are
+/// closed at their last checkpoint.
+pub fn recover(app_handle: &AppHandle) {
+    let orphans = load_active_sessions();
+    if orphans.is_empty() {
+        return;
+    }
+    let state = app_handle.state::<AppState>();
+
+    for orphan in orphans {
+        let game = state
+            .games
+            .lock()
+            .iter()
+            .find(|g| g.id == orphan.game_id)
+            .cloned();
+        let Some(game) = game else {
+            continue;
+        };
+
+        match process::find_session_process(&orphan.process) {
+            Some(pid) => {
+                log::info!("Resuming session of {} (pid {})", game.title, pid);
+                let process = TrackedProcess {
+                    pid,
+                    pid_started_at: process::start_time(pid).unwrap_or_default(),
+                    ..orphan.process
+                };
+                follow(
+                    app_handle,
+                    &game,
+                    orphan.profile_id,
+                    process,
+                    orphan.started_at,
+                    orphan.excluded,
+                    orphan.resources,
+                );
+            }
+            None => {
+                let record = SessionRecord {
+                    resources: orphan.resources.summary(),
+                    ..SessionRecord::tracked(
+                        game.id.clone(),
+                        orphan.profile_id,
+                        orphan.started_at,
+                        orphan.checkpoint_at,
+                        orphan.excluded,
+                    )
+                };
+                log::info!(
+                    "Closing interrupted session of {} at its last checkpoint ({}s)",
+                    game.title,
+                    record.active_secs
+                );
+                if let Err(e) = record_session(&state, record) {
+                    log::error!("Failed to record session for {}: {}", game.id, e);
+                }
+            }
+        }
+    }
+    checkpoint(&state);
+}
+
+/// Adds an excluded span to every running session, clamped to when each
+/// session started.
+fn exclude(state: &AppState, start: u64, end: u64, reason: ExclusionReason) {
+    let mut running = state.running_games.lock();
+    for session in running.values_mut() {
+        let start = start.max(session.started_at);
+        if end > start {
+            session.excluded.push(ExcludedSpan { start, end, reason });
+        }
+    }
+}
+
+/// Moves every running session's timestamps by `secs` after the wall clock
+/// was adjusted, so play time keeps following real elapsed time instead of
+/// growing or shrinking with the jump.
+fn shift_sessions(state: &AppState, secs: i64) {
+    let mut running = state.running_games.lock();
+    for session in running.values_mut() {
+        session.started_at = session.started_at.saturating_add_signed(secs);
+        if let Some(idle_since) = session.idle_since.as_mut() {
+            *idle_since = idle_since.saturating_add_signed(secs);
+        }
+        for span in &mut session.excluded {
+            span.start = span.start.saturating_add_signed(secs);
+            span.end = span.end.saturating_add_signed(secs);
+        }
+    }
+}
+
+/// Follows each running session's idle state, recording idle segments as
+/// excluded spans. Returns whether any session changed.
+fn track_idle(
+    state: &AppState,
+    now: u64,
+    source: &mut dyn IdleSource,
+    threshold: Duration,
+    trees: &mut HashMap<String, (u32, ProcessTree)>,
+) -> bool {
+    let sessions: Vec<(String, TrackedProcess)> = state
+        .running_games
+        .lock()
+        .values()
+        .map(|r| (r.id.clone(), r.process.clone()))
+        .collect();
+    trees.retain(|id, _| sessions.iter().any(|(running, _)| running == id));
+
+    let mut changed = false;
+    for (id, process) in sessions {
+        let (_, tree) = trees
+            .entry(id.clone())
+            .and_modify(|(pid, tree)| {
+                if *pid != process.pid {
+                    *pid = process.pid;
+                    *tree = ProcessTree::new(process.pid, process.session_tag.as_deref());
+                }
+            })
+            .or_insert_with(|| {
+                (
+                    process.pid,
+                    ProcessTree::new(process.pid, process.session_tag.as_deref()),
+                )
+            });
+        tree.refresh();
+        let pids: Vec<u32> = tree.pids().collect();
+        let idle = source.idle_for(&id, &pids);
+
+        let mut running = state.running_games.lock();
+        let Some(session) = running.get_mut(&id) else {
+            continue;
+        };
+        let was_idle = session.idle_since.is_some();
+        let finished = idle::update_idle(
+            &mut session.idle_since,
+            session.started_at,
+            now,
+            idle,
+            threshold,
+        );
+        if let Some(span) = finished {
+            log::info!("{} was idle for {}s", session.title, span.secs());
+            session.excluded.push(span);
+        }
+        changed |= was_idle != session.idle_since.is_some();
+    }
+    changed
+}
+
+/// Background thread that keeps running sessions up to date: it notices
+/// when the machine was suspended and excludes the gap, tracks idle time,
+/// and checkpoints sessions once a minute.
+///
+/// A tick that arrives much later than scheduled means the machine was
+/// asleep. Both clocks are checked because `Instant` stops during suspend
+/// on Linux and macOS but keeps running on Windows.
+pub fn spawn_ticker(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut last_reading = ClockReading::now();
+        let mut last_checkpoint = Instant::now();
+        let mut source_kind = IdleSourceKind::Off;
+        let mut source = idle::create_source(source_kind);
+        let mut trees = HashMap::new();
+
+        loop {
+            thread::sleep(TICK_INTERVAL);
+            let reading = ClockReading::now();
+            let delta = reading.since(&last_reading);
+            last_reading = reading;
+            let now = discord::get_unix_timestamp();
+
+            let state = app_handle.state::<AppState>();
+            let (running, logging): (Vec<String>, Vec<String>) = {
+                let games = state.running_games.lock();
+                (
+                    games.keys().cloned().collect(),
+                    games
+                        .values()
+                        .filter(|g| g.owns_log)
+                        .map(|g| g.id.clone())
+                        .collect(),
+                )
+            };
+            if running.is_empty() {
+                trees.clear();
+                continue;
+            }
+            for game_id in &logging {
+                game_log::enforce_limit(game_id);
+            }
+
+            let mut changed = false;
+            if delta.wall_jump_secs.abs() >= WALL_JUMP_THRESHOLD_SECS {
+                log::info!(
+                    "Wall clock moved by {}s, shifting running sessions with it",
+                    delta.wall_jump_secs
+                );
+                shift_sessions(&state, delta.wall_jump_secs);
+                changed = true;
+            }
+            if delta.suspended > SUSPEND_THRESHOLD {
+                let gap = delta.suspended.as_secs();
+                log::info!("System was suspended for {}s, excluding it from play time", gap);
+                exclude(&state, now.saturating_sub(gap), now, ExclusionReason::Suspend);
+                changed = true;
+            }
+
+            let (kind, threshold_mins) = {
+                let settings = state.settings.lock();
+                (settings.idle_source, settings.idle_threshold_mins)
+            };
+            let kind = if threshold_mins == 0 {
+                IdleSourceKind::Off
+            } else {
+                kind
+            };
+            if kind != source_kind {
+                source = idle::create_source(kind);
+                source_kind = kind;
+            }
+            let threshold = Duration::from_secs(threshold_mins * 60);
+            changed |= track_idle(&state, now, source.as_mut(), threshold, &mut trees);
+
+            if changed {
+                refresh_discord_activity(&state);
+            }
+            if changed || last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                checkpoint(&state);
+                last_checkpoint = Instant::now();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-02 00:00 UTC.
+    const JAN_2: u64 = 1_704_153_600;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn daily_minutes_carries_remainders() {
+        let days = [(date(1), 20), (date(2), 50), (date(3), 30)];
+        assert_eq!(
+            daily_minutes(50, &days),
+            vec![(date(1), 1), (date(2), 1), (date(3), 0)]
+        );
+    }
+
+    #[test]
+    fn daily_minutes_add_up_to_play_time_growth() {
+        // 05:30 to 07:30 in UTC-5 with days starting at 06:00, so half an
+        // hour falls on the 1st.
+        let mut record = SessionRecord::tracked(
+            "game".into(),
+            None,
+            JAN_2 + 10 * 3600 + 1800,
+            JAN_2 + 12 * 3600 + 1800,
+            Vec::new(),
+        );
+        record.utc_offset = -5 * 3600;
+        let days = daily_minutes(45, &record.daily_secs(6));
+        assert_eq!(days, vec![(date(1), 30), (date(2), 90)]);
+        let total: u64 = days.iter().map(|(_, minutes)| minutes).sum();
+        assert_eq!(total, (45 + record.active_secs) / 60);
+    }
+}
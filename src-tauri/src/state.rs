@@ -1,17 +1,34 @@
 use parking_lot::Mutex;
 use redb::Database;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::discord::DiscordRpc;
-use crate::models::{AppSettings, GameMetadata, RunningGame, VndbCharacter, VndbVnDetail};
+use crate::models::{
+    AppSettings, GameMetadata, LaunchRequest, PlaytimeAuditEntry, RunningGame, SessionRecord,
+    VndbCharacter, VndbVnDetail, WinePrefix,
+};
 
 pub struct AppState {
     pub games: Mutex<Vec<GameMetadata>>,
-    pub running_game: Mutex<Option<RunningGame>>,
+    pub running_games: Mutex<HashMap<String, RunningGame>>,
+    /// Games between a launch being accepted and their session beginning.
+    /// Always locked after `running_games`.
+    pub launching: Mutex<HashSet<String>>,
+    pub session_history: Mutex<Vec<SessionRecord>>,
+    /// Held while a play time change is applied and logged. Always locked
+    /// before `session_history` and `games`.
+    pub playtime_audit: Mutex<Vec<PlaytimeAuditEntry>>,
     pub settings: Mutex<AppSettings>,
+    pub wine_prefixes: Mutex<Vec<WinePrefix>>,
     pub vn_mem_cache: Mutex<HashMap<String, VndbVnDetail>>,
     pub char_mem_cache: Mutex<HashMap<String, Vec<VndbCharacter>>>,
     pub http_client: reqwest::Client,
     pub db: Option<Database>,
     pub discord_rpc: DiscordRpc,
 }
+
+/// Command line and link requests waiting for the frontend to act on them.
+/// Managed on its own before the app is built, since a second instance can
+/// hand requests over before `AppState` has been loaded.
+#[derive(Default)]
+pub struct LaunchRequests(pub Mutex<Vec<LaunchRequest>>);
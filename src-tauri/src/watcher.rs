@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+use sysinfo::System;
+use tauri::{AppHandle, Manager};
+
+use crate::process;
+use crate::session;
+use crate::state::AppState;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Background thread that picks up library games started outside the
+/// launcher. Does nothing unless `auto_detect_games` is enabled.
+pub fn spawn(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut sys = System::new();
+        // Processes we already tracked once, so a session the user stopped
+        // by hand is not picked up again right away.
+        let mut seen = HashSet::new();
+        loop {
+            thread::sleep(SCAN_INTERVAL);
+            let state = app_handle.state::<AppState>();
+            if !state.settings.lock().auto_detect_games {
+                continue;
+            }
+
+            let games: Vec<_> = {
+                let running = state.running_games.lock();
+                state
+                    .games
+                    .lock()
+                    .iter()
+                    .filter(|g| !running.contains_key(&g.id))
+                    .cloned()
+                    .collect()
+            };
+            let detected = process::find_external_game(&mut sys, &games, &seen);
+            seen.retain(|&(pid, started_at)| process::is_same_process(&sys, pid, started_at));
+            let Some(detected) = detected else {
+                continue;
+            };
+            seen.insert((detected.pid, detected.started_at));
+            let Some(game) = games.iter().find(|g| g.id == detected.game_id) else {
+                continue;
+            };
+            // The game may have been launched since the snapshot above.
+            let slot = session::reserve(&state, game);
+            if slot.is_ok() {
+                session::track_external(&app_handle, game, detected.profile_id, detected.pid);
+            }
+        }
+    });
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use crate::database::get_default_prefixes_dir;
+use crate::error::{AppError, AppResult};
+use crate::launcher::LaunchPlan;
+use crate::models::{RunnerConfig, RunnerKind, WineArch, WinePrefix};
+
+// Programs shipped inside Wine itself, run through the `wine` loader
+const WINE_BUILTINS: &[&str] = &[
+    "winecfg",
+    "regedit",
+    "wineboot",
+    "winefile",
+    "wineconsole",
+    "control",
+    "taskmgr",
+    "uninstaller",
+    "explorer",
+    "notepad",
+    "msiexec",
+    "cmd",
+    "reg",
+];
+
+const DLL_OVERRIDES_KEY: &str = r"HKCU\Software\Wine\DllOverrides";
+const INTERNATIONAL_KEY: &str = r"HKCU\Control Panel\International";
+const NLS_LANGUAGE_KEY: &str = r"HKLM\System\CurrentControlSet\Control\Nls\Language";
+const NLS_CODEPAGE_KEY: &str = r"HKLM\System\CurrentControlSet\Control\Nls\CodePage";
+
+const DLL_OVERRIDE_MODES: &[&str] = &["n", "b", "n,b", "b,n", "native", "builtin", ""];
+
+/// Windows language id and ANSI code page for the locales we know how to set.
+fn locale_ids(locale: &str) -> Option<(&'static str, &'static str)> {
+    match locale {
+        "ja_JP" => Some(("0411", "932")),
+        "zh_CN" => Some(("0804", "936")),
+        "zh_TW" => Some(("0404", "950")),
+        "ko_KR" => Some(("0412", "949")),
+        "en_US" => Some(("0409", "1252")),
+        _ => None,
+    }
+}
+
+pub fn ensure_supported() -> AppResult<()> {
+    if cfg!(windows) {
+        return Err(AppError::Validation(
+            "Wine prefixes are not available on Windows".into(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn wine_binary(prefix: &WinePrefix) -> PathBuf {
+    match prefix.wine_path.as_deref().map(str::trim) {
+        Some(path) if !path.is_empty() => {
+            let path = Path::new(path);
+            if path.is_dir() {
+                path.join("bin").join("wine")
+            } else {
+                path.to_path_buf()
+            }
+        }
+        _ => PathBuf::from("wine"),
+    }
+}
+
+/// `WINEDLLOVERRIDES` value for a prefix, e.g. `d3d9=n,b;dinput8=n`.
+pub fn dll_overrides_env(overrides: &HashMap<String, String>) -> Option<String> {
+    if overrides.is_empty() {
+        return None;
+    }
+    let mut entries: Vec<String> = overrides
+        .iter()
+        .map(|(dll, mode)| format!("{}={}", dll, mode))
+        .collect();
+    entries.sort();
+    Some(entries.join(";"))
+}
+
+fn prefix_env(cmd: &mut Command, prefix: &WinePrefix) {
+    cmd.env("WINEPREFIX", &prefix.path)
+        .env(
+            "WINEARCH",
+            match prefix.arch {
+                WineArch::Win32 => "win32",
+                WineArch::Win64 => "win64",
+            },
+        )
+        .env("WINEDEBUG", "-all");
+}
+
+fn wine_command(prefix: &WinePrefix) -> Command {
+    let mut cmd = Command::new(wine_binary(prefix));
+    prefix_env(&mut cmd, prefix);
+    cmd
+}
+
+fn run_wine(prefix: &WinePrefix, args: &[&str]) -> AppResult<()> {
+    let output = wine_command(prefix)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| {
+            AppError::ProcessLaunch(format!(
+                "Failed to run {}: {}",
+                wine_binary(prefix).display(),
+                e
+            ))
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::ProcessLaunch(format!(
+            "wine {} failed ({}): {}",
+            args.join(" "),
+            output.status,
+            stderr.trim()
+        )));
+    }
+    Ok(())
+}
+
+fn reg_add(prefix: &WinePrefix, key: &str, name: &str, value: &str) -> AppResult<()> {
+    run_wine(
+        prefix,
+        &[
+            "reg", "add", key, "/v", name, "/t", "REG_SZ", "/d", value, "/f",
+        ],
+    )
+}
+
+fn reg_delete(prefix: &WinePrefix, key: &str, name: &str) -> AppResult<()> {
+    run_wine(prefix, &["reg", "delete", key, "/v", name, "/f"])
+}
+
+fn looks_like_prefix(path: &Path) -> bool {
+    path.join("system.reg").exists()
+}
+
+/// Checks a folder can hold a new prefix. Returns `true` when the folder is
+/// missing or empty, `false` when it already is a Wine prefix.
+pub fn check_new_prefix_dir(path: &Path) -> AppResult<bool> {
+    if !path.exists() {
+        return Ok(true);
+    }
+    if !path.is_dir() {
+        return Err(AppError::Validation(format!(
+            "Not a folder: {}",
+            path.display()
+        )));
+    }
+    if fs::read_dir(path)?.next().is_none() {
+        return Ok(true);
+    }
+    if looks_like_prefix(path) {
+        return Ok(false);
+    }
+    Err(AppError::Validation(format!(
+        "{} is not empty and is not a Wine prefix",
+        path.display()
+    )))
+}
+
+/// Creates the prefix folder and lets `wineboot` populate it.
+pub fn create_prefix(prefix: &WinePrefix) -> AppResult<()> {
+    fs::create_dir_all(&prefix.path)?;
+    run_wine(prefix, &["wineboot", "--init"])?;
+    log::info!("Created Wine prefix {} at {}", prefix.name, prefix.path);
+    Ok(())
+}
+
+fn is_default_prefix_dir(path: &Path) -> bool {
+    let root = get_default_prefixes_dir();
+    let root = fs::canonicalize(&root).unwrap_or(root);
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    path != root && path.starts_with(&root)
+}
+
+/// Removes a prefix folder. Only folders the launcher created itself are
+/// touched, and never anything that doesn't look like a prefix.
+pub fn delete_prefix_files(prefix: &WinePrefix) -> AppResult<()> {
+    let path = Path::new(&prefix.path);
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if !prefix.created_by_launcher && !is_default_prefix_dir(path) {
+        return Err(AppError::Validation(format!(
+            "Refusing to delete {}: the launcher did not create this prefix",
+            prefix.path
+        )));
+    }
+    let is_empty = fs::read_dir(path)?.next().is_none();
+    if !is_empty && !looks_like_prefix(path) {
+        return Err(AppError::Validation(format!(
+            "Refusing to delete {}: it does not look like a Wine prefix",
+            prefix.path
+        )));
+    }
+
+    fs::remove_dir_all(path)?;
+    log::info!("Deleted Wine prefix files at {}", prefix.path);
+    Ok(())
+}
+
+pub fn validate_dll_overrides(
+    overrides: &HashMap<String, String>,
+) -> AppResult<HashMap<String, String>> {
+    let mut cleaned = HashMap::new();
+    for (dll, mode) in overrides {
+        let dll = dll.trim().to_lowercase();
+        let dll = dll.trim_end_matches(".dll").to_string();
+        if dll.is_empty() || dll.contains(['=', ';', ',', '\\', '/']) {
+            return Err(AppError::Validation(format!("Invalid DLL name: {}", dll)));
+        }
+        let mode = mode.trim().replace(' ', "");
+        if !DLL_OVERRIDE_MODES.contains(&mode.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Invalid override mode for {}: {}",
+                dll, mode
+            )));
+        }
+        cleaned.insert(dll, mode);
+    }
+    Ok(cleaned)
+}
+
+/// Writes the overrides into the prefix registry, removing ones that were dropped.
+pub fn apply_dll_overrides(
+    prefix: &WinePrefix,
+    overrides: &HashMap<String, String>,
+) -> AppResult<()> {
+    for removed in prefix.dll_overrides.keys() {
+        if !overrides.contains_key(removed) {
+            reg_delete(prefix, DLL_OVERRIDES_KEY, removed)?;
+        }
+    }
+    for (dll, mode) in overrides {
+        reg_add(prefix, DLL_OVERRIDES_KEY, dll, mode)?;
+    }
+    Ok(())
+}
+
+pub fn validate_locale(locale: &str) -> AppResult<()> {
+    if locale_ids(locale).is_none() {
+        return Err(AppError::Validation(format!(
+            "Unsupported prefix locale: {}",
+            locale
+        )));
+    }
+    Ok(())
+}
+
+/// Sets the registry locale and code page keys; `None` restores US English.
+pub fn apply_locale(prefix: &WinePrefix, locale: Option<&str>) -> AppResult<()> {
+    let (lang_id, codepage) = locale_ids(locale.unwrap_or("en_US"))
+        .ok_or_else(|| AppError::Validation("Unsupported prefix locale".into()))?;
+
+    reg_add(
+        prefix,
+        INTERNATIONAL_KEY,
+        "Locale",
+        &format!("0000{}", lang_id),
+    )?;
+    reg_add(prefix, NLS_LANGUAGE_KEY, "Default", lang_id)?;
+    reg_add(prefix, NLS_LANGUAGE_KEY, "InstallLanguage", lang_id)?;
+    reg_add(prefix, NLS_CODEPAGE_KEY, "ACP", codepage)?;
+    reg_add(prefix, NLS_CODEPAGE_KEY, "OEMCP", codepage)?;
+    Ok(())
+}
+
+/// Starts a tool inside the prefix. Wine builtins and Windows binaries go
+/// through the loader; anything else (e.g. `winetricks`) runs natively with
+/// `WINEPREFIX` set.
+pub fn spawn_tool(prefix: &WinePrefix, tool: &str, args: &[String]) -> AppResult<Child> {
+    let tool = tool.trim();
+    if tool.is_empty() {
+        return Err(AppError::Validation("Tool cannot be empty".into()));
+    }
+
+    let extension = Path::new(tool)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let through_wine = WINE_BUILTINS.contains(&tool.to_lowercase().as_str())
+        || matches!(extension.as_deref(), Some("exe" | "msi" | "bat"));
+
+    let mut cmd = if through_wine {
+        let mut cmd = wine_command(prefix);
+        cmd.arg(tool);
+        cmd
+    } else {
+        let mut cmd = Command::new(tool);
+        prefix_env(&mut cmd, prefix);
+        if prefix.wine_path.is_some() {
+            cmd.env("WINE", wine_binary(prefix));
+        }
+        cmd
+    };
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    log::info!(
+        "Running {} {:?} in prefix {} ({})",
+        tool,
+        args,
+        prefix.name,
+        prefix.path
+    );
+
+    cmd.spawn()
+        .map_err(|e| AppError::ProcessLaunch(format!("Failed to run {}: {}", tool, e)))
+}
+
+/// Points a launch at a managed prefix: the runner uses the prefix folder
+/// (and its Wine build when the game has no Wine runner of its own) and the
+/// prefix DLL overrides are exported unless the game sets its own.
+pub fn apply_prefix(
+    mut runner: RunnerConfig,
+    plan: &mut LaunchPlan,
+    prefix: &WinePrefix,
+) -> AppResult<RunnerConfig> {
+    match runner.kind {
+        RunnerKind::Proton => {
+            return Err(AppError::Validation(
+                "Managed Wine prefixes cannot be used with the Proton runner".into(),
+            ));
+        }
+        RunnerKind::Native => {
+            runner.kind = if prefix.wine_path.is_some() {
+                RunnerKind::CustomWine
+            } else {
+                RunnerKind::SystemWine
+            };
+            runner.path = prefix.wine_path.clone();
+        }
+        RunnerKind::SystemWine | RunnerKind::CustomWine => {}
+    }
+    runner.prefix = Some(prefix.path.clone());
+
+    if let Some(overrides) = dll_overrides_env(&prefix.dll_overrides) {
+        if !plan.env.iter().any(|(k, _)| k == "WINEDLLOVERRIDES") {
+            plan.env.push(("WINEDLLOVERRIDES".into(), overrides));
+            plan.env.sort();
+        }
+    }
+
+    Ok(runner)
+}
@@ -1,11 +1,12 @@
 import { createSignal, For, Show, onCleanup, onMount } from "solid-js";
 import { Router, Route, useNavigate, useParams } from "@solidjs/router";
 import { open } from "@tauri-apps/plugin-dialog";
-import { Search, X, User, RefreshCw } from "lucide-solid";
+import { Search, X, User, RefreshCw, AlertCircle } from "lucide-solid";
 
 import type { VndbImage, VndbSearchResult } from "./bindings";
 import { useLibraryFilters } from "./hooks/useLibraryFilters";
 import { useUpdater } from "./hooks/useUpdater";
+import { useLaunchRequests } from "./hooks/useLaunchRequests";
 
 import { GameProvider, SettingsProvider, VndbProvider, useGame, useSettings, useVndb } from "./context";
 import { Library } from "./views/Library";
@@ -86,7 +87,7 @@ function LibraryPage() {
       <Library
         games={game.games()}
         filteredGames={libraryFilters.filteredGames()}
-        runningGame={game.runningGame()}
+        runningGames={game.runningGames()}
         loading={game.loading()}
         authUser={settings.authUser()}
         searchQuery={libraryFilters.searchQuery()}
@@ -99,7 +100,7 @@ function LibraryPage() {
         onShowHiddenChange={libraryFilters.setShowHidden}
         formatPlayTime={formatPlayTime}
         onAddGame={addGame}
-        onStopTracking={game.stopTracking}
+        onStopTracking={(id) => game.stopTracking(id)}
         onLaunchGame={game.launchGame}
         onRemoveGame={game.removeGame}
         onSearchGame={(g) => {
@@ -239,7 +240,7 @@ function DetailPage() {
           vnDetail={vndb.vnDetail()!}
           characters={vndb.characters()}
           userVn={vndb.userVn()}
-          runningGame={game.runningGame()}
+          runningGames={game.runningGames()}
           settings={settings.settings()}
           showSpoilers={showSpoilers()}
           setShowSpoilers={setShowSpoilers}
@@ -248,6 +249,7 @@ function DetailPage() {
           onRefresh={refreshDetail}
           onSettings={() => setShowSettings(true)}
           onLaunchGame={game.launchGame}
+          onStopTracking={(id) => game.stopTracking(id)}
           onSetStatus={setStatus}
           onSetVote={setVote}
           formatPlayTime={formatPlayTime}
@@ -541,11 +543,32 @@ function SearchModal(props: {
   );
 }
 
+function LaunchRequestHandler() {
+  const { errorMessage, dismissError } = useLaunchRequests();
+  return (
+    <Show when={errorMessage()}>
+      <div class="fixed top-20 left-1/2 z-[100] animate-fade-in-down">
+        <div class="flex items-center gap-3 px-5 py-3.5 bg-gradient-to-r from-red-900/95 to-red-800/95 backdrop-blur-xl border border-red-500/30 rounded-2xl shadow-2xl shadow-red-900/40">
+          <AlertCircle class="w-5 h-5 text-red-400 flex-shrink-0" />
+          <span class="text-red-100 font-medium text-sm">{errorMessage()}</span>
+          <button
+            onClick={dismissError}
+            class="ml-2 p-1 hover:bg-red-700/50 rounded-lg transition-colors"
+          >
+            <X class="w-4 h-4 text-red-300" />
+          </button>
+        </div>
+      </div>
+    </Show>
+  );
+}
+
 function AppLayout(props: { children?: any }) {
   return (
     <GameProvider>
       <SettingsProvider>
         <VndbProvider>
+          <LaunchRequestHandler />
           <div class="h-screen flex flex-col bg-[#0F172A]">
             <TitleBar />
             <div class="flex-1 overflow-hidden relative">
@@ -2,12 +2,75 @@ import { commands } from "../bindings";
 
 export const getAllGames = () => commands.getAllGames();
 export const addLocalGame = (path: string) => commands.addLocalGame(path);
+export const addUriGame = (title: string, uri: string, trackExecutable: string) =>
+    commands.addUriGame(title, uri, trackExecutable);
+export const scanLibrary = (roots: string[]) => commands.scanLibrary(roots);
+export const importScannedGames = (
+    candidates: Parameters<typeof commands.importScannedGames>[0]
+) => commands.importScannedGames(candidates);
 export const removeGame = (id: string) => commands.removeGame(id);
 export const updateGame = (game: Parameters<typeof commands.updateGame>[0]) =>
     commands.updateGame(game);
 export const setGameHidden = (id: string, hidden: boolean) =>
     commands.setGameHidden(id, hidden);
+export const setLaunchOptions = (
+    id: string,
+    options: Parameters<typeof commands.setLaunchOptions>[1]
+) => commands.setLaunchOptions(id, options);
+export const addLaunchProfile = (
+    gameId: string,
+    name: string,
+    path: string,
+    options: Parameters<typeof commands.addLaunchProfile>[3]
+) => commands.addLaunchProfile(gameId, name, path, options);
+export const updateLaunchProfile = (
+    gameId: string,
+    profile: Parameters<typeof commands.updateLaunchProfile>[1]
+) => commands.updateLaunchProfile(gameId, profile);
+export const removeLaunchProfile = (gameId: string, profileId: string) =>
+    commands.removeLaunchProfile(gameId, profileId);
+export const setDefaultProfile = (gameId: string, profileId: string | null) =>
+    commands.setDefaultProfile(gameId, profileId);
+export const setGameRunner = (
+    gameId: string,
+    runner: Parameters<typeof commands.setGameRunner>[1]
+) => commands.setGameRunner(gameId, runner);
+export const setGameJapaneseLocale = (gameId: string, enabled: boolean) =>
+    commands.setGameJapaneseLocale(gameId, enabled);
+export const setGameHooks = (
+    gameId: string,
+    hooks: Parameters<typeof commands.setGameHooks>[1]
+) => commands.setGameHooks(gameId, hooks);
+export const setGameWrappers = (
+    gameId: string,
+    wrappers: string[],
+    useGlobalWrappers: boolean,
+    priority: Parameters<typeof commands.setGameWrappers>[3]
+) => commands.setGameWrappers(gameId, wrappers, useGlobalWrappers, priority);
+export const setGameSandbox = (
+    gameId: string,
+    sandbox: Parameters<typeof commands.setGameSandbox>[1]
+) => commands.setGameSandbox(gameId, sandbox);
+export const setLaunchTarget = (
+    gameId: string,
+    target: Parameters<typeof commands.setLaunchTarget>[1]
+) => commands.setLaunchTarget(gameId, target);
+export const setTrackExecutable = (gameId: string, name: string | null) =>
+    commands.setTrackExecutable(gameId, name);
 export const launchGame = (id: string) => commands.launchGame(id);
-export const stopTracking = () => commands.stopTracking();
+export const takeLaunchRequests = () => commands.takeLaunchRequests();
+export const resolveLaunchRequest = (
+    request: Parameters<typeof commands.resolveLaunchRequest>[0]
+) => commands.resolveLaunchRequest(request);
+export const launchGameProfile = (id: string, profileId: string) =>
+    commands.launchGameProfile(id, profileId);
+export const attachToProcess = (gameId: string, pid: number) =>
+    commands.attachToProcess(gameId, pid);
+export const stopTracking = (gameId: string) => commands.stopTracking(gameId);
+export const forceStopGame = (gameId: string) => commands.forceStopGame(gameId);
+export const getGameLog = (gameId: string) => commands.getGameLog(gameId);
+export const previewLaunch = (gameId: string, profileId: string | null) =>
+    commands.previewLaunch(gameId, profileId);
 export const pollRunningGame = () => commands.pollRunningGame();
 export const getElapsedTime = () => commands.getElapsedTime();
+export const getResourceUsage = () => commands.getResourceUsage();
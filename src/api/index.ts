@@ -1,3 +1,5 @@
 export * from "./games";
 export * from "./vndb";
 export * from "./settings";
+export * from "./wine";
+export * from "./sessions";
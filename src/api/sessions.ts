@@ -0,0 +1,27 @@
+import { commands } from "../bindings";
+
+export const listSessions = (gameId: string | null, from: number | null, to: number | null) =>
+    commands.listSessions(gameId, from, to);
+export const addManualSession = (
+    gameId: string,
+    startedAt: number,
+    activeSecs: number,
+    reason: string
+) => commands.addManualSession(gameId, startedAt, activeSecs, reason);
+export const editSession = (
+    sessionId: string,
+    startedAt: number,
+    activeSecs: number,
+    reason: string
+) => commands.editSession(sessionId, startedAt, activeSecs, reason);
+export const deleteSession = (sessionId: string, reason: string) =>
+    commands.deleteSession(sessionId, reason);
+export const adjustDailyPlaytime = (
+    gameId: string,
+    date: string,
+    minutes: number,
+    reason: string
+) => commands.adjustDailyPlaytime(gameId, date, minutes, reason);
+export const listPlaytimeAudit = (gameId: string | null) => commands.listPlaytimeAudit(gameId);
+export const revertPlaytimeChange = (auditId: string, reason: string | null) =>
+    commands.revertPlaytimeChange(auditId, reason);
@@ -8,4 +8,27 @@ export const setDiscordRpcEnabled = (enabled: boolean) =>
     commands.setDiscordRpcEnabled(enabled);
 export const setDiscordRpcButtons = (vndbGame: boolean, vndbProfile: boolean, github: boolean) =>
     commands.setDiscordRpcButtons(vndbGame, vndbProfile, github);
+export const setDefaultRunner = (runner: Parameters<typeof commands.setDefaultRunner>[0]) =>
+    commands.setDefaultRunner(runner);
+export const setLocaleEmulatorCommand = (command: string | null) =>
+    commands.setLocaleEmulatorCommand(command);
+export const setGlobalHooks = (
+    hooks: Parameters<typeof commands.setGlobalHooks>[0],
+    timeoutSecs: number
+) => commands.setGlobalHooks(hooks, timeoutSecs);
+export const setGlobalWrappers = (
+    wrappers: string[],
+    priority: Parameters<typeof commands.setGlobalWrappers>[1]
+) => commands.setGlobalWrappers(wrappers, priority);
+export const setEmulators = (emulators: Parameters<typeof commands.setEmulators>[0]) =>
+    commands.setEmulators(emulators);
+export const setAutoDetectGames = (enabled: boolean) =>
+    commands.setAutoDetectGames(enabled);
+export const setIdleDetection = (
+    source: Parameters<typeof commands.setIdleDetection>[0],
+    thresholdMins: number
+) => commands.setIdleDetection(source, thresholdMins);
+export const setResourceSampling = (intervalSecs: number) =>
+    commands.setResourceSampling(intervalSecs);
+export const setDayStartHour = (hour: number) => commands.setDayStartHour(hour);
 export const initApp = () => commands.initApp();
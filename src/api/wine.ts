@@ -0,0 +1,20 @@
+import { commands } from "../bindings";
+import type { WineArch } from "../bindings";
+
+export const listWinePrefixes = () => commands.listWinePrefixes();
+export const createWinePrefix = (
+    name: string,
+    path: string | null,
+    arch: WineArch,
+    winePath: string | null
+) => commands.createWinePrefix(name, path, arch, winePath);
+export const deleteWinePrefix = (id: string, deleteFiles: boolean) =>
+    commands.deleteWinePrefix(id, deleteFiles);
+export const assignWinePrefix = (gameId: string, prefixId: string | null) =>
+    commands.assignWinePrefix(gameId, prefixId);
+export const setPrefixDllOverrides = (id: string, overrides: Record<string, string>) =>
+    commands.setPrefixDllOverrides(id, overrides);
+export const setPrefixLocale = (id: string, locale: string | null) =>
+    commands.setPrefixLocale(id, locale);
+export const runInPrefix = (id: string, tool: string, args: string[]) =>
+    commands.runInPrefix(id, tool, args);
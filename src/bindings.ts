@@ -9,6 +9,24 @@ export const commands = {
 async initApp() : Promise<void> {
     await TAURI_INVOKE("init_app");
 },
+/**
+ * Requests from the command line of this or a later invocation, oldest
+ * first. Each request is returned once.
+ */
+async takeLaunchRequests() : Promise<LaunchRequest[]> {
+    return await TAURI_INVOKE("take_launch_requests");
+},
+/**
+ * The library game a request refers to.
+ */
+async resolveLaunchRequest(request: LaunchRequest) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resolve_launch_request", { request }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getAllGames() : Promise<GameMetadata[]> {
     return await TAURI_INVOKE("get_all_games");
 },
@@ -20,6 +38,30 @@ async addLocalGame(path: string) : Promise<Result<GameMetadata, AppError>> {
     else return { status: "error", error: e  as any };
 }
 },
+async addUriGame(title: string, uri: string, trackExecutable: string) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_uri_game", { title, uri, trackExecutable }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async scanLibrary(roots: string[]) : Promise<Result<ScanCandidate[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("scan_library", { roots }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async importScannedGames(candidates: ScanCandidate[]) : Promise<Result<GameMetadata[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_scanned_games", { candidates }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async removeGame(id: string) : Promise<Result<null, AppError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("remove_game", { id }) };
@@ -151,20 +193,131 @@ async launchGame(id: string) : Promise<Result<null, AppError>> {
     else return { status: "error", error: e  as any };
 }
 },
-async stopTracking() : Promise<Result<number, AppError>> {
+async launchGameProfile(id: string, profileId: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("launch_game_profile", { id, profileId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopTracking(gameId: string) : Promise<Result<number, AppError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("stop_tracking") };
+    return { status: "ok", data: await TAURI_INVOKE("stop_tracking", { gameId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async pollRunningGame() : Promise<string | null> {
+/**
+ * Terminates a running game: its processes are asked to exit and killed if
+ * they are still around after a grace period. The session then ends as
+ * usual, with `force_stopped` set on its `game-exited` event.
+ */
+async forceStopGame(gameId: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("force_stop_game", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The end of the output captured from the game's last run, if any.
+ */
+async getGameLog(gameId: string) : Promise<Result<string | null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_game_log", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The command a launch would run, with every wrapper applied, without
+ * starting anything.
+ */
+async previewLaunch(gameId: string, profileId: string | null) : Promise<Result<LaunchPreview, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_launch", { gameId, profileId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async pollRunningGame() : Promise<RunningSession[]> {
     return await TAURI_INVOKE("poll_running_game");
 },
-async getElapsedTime() : Promise<number> {
+async getElapsedTime() : Promise<Partial<{ [key in string]: number }>> {
     return await TAURI_INVOKE("get_elapsed_time");
 },
+/**
+ * Latest CPU and memory sample of each running game that has one.
+ */
+async getResourceUsage() : Promise<Partial<{ [key in string]: ResourceUsage }>> {
+    return await TAURI_INVOKE("get_resource_usage");
+},
+async attachToProcess(gameId: string, pid: number) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("attach_to_process", { gameId, pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sessions overlapping `from..to` (unix seconds), newest first. Either bound
+ * and the game filter may be omitted.
+ */
+async listSessions(gameId: string | null, from: number | null, to: number | null) : Promise<SessionRecord[]> {
+    return await TAURI_INVOKE("list_sessions", { gameId, from, to });
+},
+async addManualSession(gameId: string, startedAt: number, activeSecs: number, reason: string) : Promise<Result<PlaytimeAuditEntry, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_manual_session", { gameId, startedAt, activeSecs, reason }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async editSession(sessionId: string, startedAt: number, activeSecs: number, reason: string) : Promise<Result<PlaytimeAuditEntry, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("edit_session", { sessionId, startedAt, activeSecs, reason }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteSession(sessionId: string, reason: string) : Promise<Result<PlaytimeAuditEntry, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_session", { sessionId, reason }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async adjustDailyPlaytime(gameId: string, date: string, minutes: number, reason: string) : Promise<Result<PlaytimeAuditEntry, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("adjust_daily_playtime", { gameId, date, minutes, reason }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Play time changes, newest first.
+ */
+async listPlaytimeAudit(gameId: string | null) : Promise<PlaytimeAuditEntry[]> {
+    return await TAURI_INVOKE("list_playtime_audit", { gameId });
+},
+async revertPlaytimeChange(auditId: string, reason: string | null) : Promise<Result<PlaytimeAuditEntry, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("revert_playtime_change", { auditId, reason }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async setGameHidden(id: string, hidden: boolean) : Promise<Result<null, AppError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("set_game_hidden", { id, hidden }) };
@@ -173,6 +326,102 @@ async setGameHidden(id: string, hidden: boolean) : Promise<Result<null, AppError
     else return { status: "error", error: e  as any };
 }
 },
+async setLaunchOptions(id: string, options: LaunchOptions) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_launch_options", { id, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addLaunchProfile(gameId: string, name: string, path: string, options: LaunchOptions) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_launch_profile", { gameId, name, path, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateLaunchProfile(gameId: string, profile: LaunchProfile) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_launch_profile", { gameId, profile }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeLaunchProfile(gameId: string, profileId: string) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_launch_profile", { gameId, profileId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setDefaultProfile(gameId: string, profileId: string | null) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_default_profile", { gameId, profileId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setGameRunner(gameId: string, runner: RunnerConfig | null) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_game_runner", { gameId, runner }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setGameJapaneseLocale(gameId: string, enabled: boolean) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_game_japanese_locale", { gameId, enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setGameHooks(gameId: string, hooks: HookCommands) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_game_hooks", { gameId, hooks }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setTrackExecutable(gameId: string, name: string | null) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_track_executable", { gameId, name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setGameWrappers(gameId: string, wrappers: string[], useGlobalWrappers: boolean, priority: ProcessPriority | null) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_game_wrappers", { gameId, wrappers, useGlobalWrappers, priority }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setGameSandbox(gameId: string, sandbox: SandboxConfig) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_game_sandbox", { gameId, sandbox }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLaunchTarget(gameId: string, target: LaunchTarget) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_launch_target", { gameId, target }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async setDiscordRpcEnabled(enabled: boolean) : Promise<Result<null, AppError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("set_discord_rpc_enabled", { enabled }) };
@@ -188,6 +437,129 @@ async setDiscordRpcButtons(vndbGame: boolean, vndbProfile: boolean, github: bool
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+async setDefaultRunner(runner: RunnerConfig) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_default_runner", { runner }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLocaleEmulatorCommand(command: string | null) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_locale_emulator_command", { command }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setGlobalHooks(hooks: HookCommands, timeoutSecs: number) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_global_hooks", { hooks, timeoutSecs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setGlobalWrappers(wrappers: string[], priority: ProcessPriority) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_global_wrappers", { wrappers, priority }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setEmulators(emulators: EmulatorConfig[]) : Promise<Result<EmulatorConfig[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_emulators", { emulators }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAutoDetectGames(enabled: boolean) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_auto_detect_games", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setIdleDetection(source: IdleSourceKind, thresholdMins: number) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_idle_detection", { source, thresholdMins }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setDayStartHour(hour: number) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_day_start_hour", { hour }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setResourceSampling(intervalSecs: number) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_resource_sampling", { intervalSecs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listWinePrefixes() : Promise<WinePrefix[]> {
+    return await TAURI_INVOKE("list_wine_prefixes");
+},
+async createWinePrefix(name: string, path: string | null, arch: WineArch, winePath: string | null) : Promise<Result<WinePrefix, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_wine_prefix", { name, path, arch, winePath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteWinePrefix(id: string, deleteFiles: boolean) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_wine_prefix", { id, deleteFiles }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async assignWinePrefix(gameId: string, prefixId: string | null) : Promise<Result<GameMetadata, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("assign_wine_prefix", { gameId, prefixId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setPrefixDllOverrides(id: string, overrides: Partial<{ [key in string]: string }>) : Promise<Result<WinePrefix, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_prefix_dll_overrides", { id, overrides }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setPrefixLocale(id: string, locale: string | null) : Promise<Result<WinePrefix, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_prefix_locale", { id, locale }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async runInPrefix(id: string, tool: string, args: string[]) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_in_prefix", { id, tool, args }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
@@ -202,9 +574,206 @@ async setDiscordRpcButtons(vndbGame: boolean, vndbProfile: boolean, github: bool
 /** user-defined types **/
 
 export type AppError = { kind: "Io"; message: string } | { kind: "Json"; message: string } | { kind: "Http"; message: string } | { kind: "Database"; message: string } | { kind: "Bincode"; message: string } | { kind: "NotFound"; message: string } | { kind: "VndbApi"; message: string } | { kind: "AuthRequired"; message: string } | { kind: "ProcessLaunch"; message: string } | { kind: "Validation"; message: string }
-export type AppSettings = { vndb_token: string | null; vndb_user_id: string | null; blur_nsfw: boolean; discord_rpc_enabled?: boolean; discord_btn_vndb_game?: boolean; discord_btn_vndb_profile?: boolean; discord_btn_github?: boolean }
+export type AppSettings = { vndb_token: string | null; vndb_user_id: string | null; blur_nsfw: boolean; discord_rpc_enabled?: boolean; discord_btn_vndb_game?: boolean; discord_btn_vndb_profile?: boolean; discord_btn_github?: boolean; default_runner?: RunnerConfig; 
+/**
+ * Windows wrapper template for Japanese locale launches, e.g.
+ * `"C:\Tools\LE\LEProc.exe" -run "{exe}" {args}`.
+ */
+locale_emulator_command?: string | null; hooks?: HookCommands; hook_timeout_secs?: number; 
+/**
+ * Watch running processes for library games started outside the launcher.
+ */
+auto_detect_games?: boolean; idle_source?: IdleSourceKind; 
+/**
+ * Minutes without activity before a session counts as idle. Settings
+ * saved with 0 before it was validated still turn idle detection off.
+ */
+idle_threshold_mins?: number; 
+/**
+ * Local hour (0-23) at which a new day starts for daily play time, so
+ * late-night sessions can count toward the previous day.
+ */
+day_start_hour?: number; 
+/**
+ * Commands every game is launched through, outermost first.
+ */
+wrappers?: string[]; priority?: ProcessPriority; 
+/**
+ * Seconds between CPU and memory samples of running games; 0 turns
+ * resource monitoring off.
+ */
+resource_sample_secs?: number; emulators?: EmulatorConfig[] }
 export type DailyPlaytimeData = { games: Partial<{ [key in string]: Partial<{ [key in string]: number }> }> }
-export type GameMetadata = { id: string; title: string; path: string; vndb_id: string | null; cover_url: string | null; play_time: number; is_finished: boolean; last_played?: string | null; is_hidden?: boolean }
+/**
+ * An emulator console games can be launched through. `args` is a command
+ * line template where `{rom}` is replaced with the game's image.
+ */
+export type EmulatorConfig = { id: string; name: string; executable: string; args?: string; 
+/**
+ * ROM extensions (without the dot) this emulator is suggested for.
+ */
+extensions?: string[] }
+/**
+ * A stretch of a session, in unix seconds, that does not count as play time.
+ */
+export type ExcludedSpan = { start: number; end: number; reason: ExclusionReason }
+export type ExclusionReason = "Suspend" | "Idle"
+export type GameMetadata = { id: string; title: string; path: string; vndb_id: string | null; cover_url: string | null; play_time: number; is_finished: boolean; last_played?: string | null; is_hidden?: boolean; launch_options?: LaunchOptions; profiles?: LaunchProfile[]; default_profile_id?: string | null; runner?: RunnerConfig | null; wine_prefix_id?: string | null; japanese_locale?: boolean; hooks?: HookCommands; 
+/**
+ * Executable name to wait for when the launcher detaches the game
+ * from its own process tree.
+ */
+track_executable?: string | null; 
+/**
+ * Commands the game is launched through, outermost first, e.g.
+ * `gamescope -W 1920 --`. Run inside the global wrappers.
+ */
+wrappers?: string[]; use_global_wrappers?: boolean; 
+/**
+ * Overrides the global process priority.
+ */
+priority?: ProcessPriority | null; sandbox?: SandboxConfig; target?: LaunchTarget }
+/**
+ * Shell commands run before a game is spawned and after it exits.
+ */
+export type HookCommands = { pre_launch?: string | null; post_exit?: string | null }
+/**
+ * Where idle time comes from: input inactivity, the game's CPU usage, or
+ * input when available with CPU usage as the fallback.
+ */
+export type IdleSourceKind = "Auto" | "Input" | "Cpu" | "Off"
+export type LaunchOptions = { args?: string[]; working_dir?: string | null; env?: Partial<{ [key in string]: string }> }
+/**
+ * What launching a game would run, for debugging launch settings.
+ */
+export type LaunchPreview = { command_line: string; cwd: string; 
+/**
+ * `KEY=VALUE` pairs set on top of the launcher's own environment.
+ */
+env: string[] }
+export type LaunchProfile = { id: string; name: string; path: string; launch_options?: LaunchOptions; target?: LaunchTarget }
+/**
+ * Something the command line or an `alka://` link asks the running
+ * launcher to do, e.g. `--launch <game id>` passed to a second invocation.
+ */
+export type LaunchRequest = { type: "Launch"; game_id: string; from_link?: boolean } | { type: "ShowGame"; game_id: string } | 
+/**
+ * Opens the library game linked to a VNDB id such as `v1234`.
+ */
+{ type: "ShowVndb"; vndb_id: string }
+/**
+ * What a game or profile `path` points at.
+ */
+export type LaunchTarget = { type: "Executable" } | 
+/**
+ * A script run through `interpreter`, e.g. `bash` or `python3`, or one
+ * picked from its extension when unset.
+ */
+{ type: "Script"; interpreter: string | null } | 
+/**
+ * A URI such as `steam://rungameid/...` handed to the OS opener. The
+ * session follows the game's "track executable" once it shows up.
+ */
+{ type: "Uri" } | 
+/**
+ * A ROM or disc image run through one of the emulators in settings.
+ */
+{ type: "Rom"; emulator_id: string | null }
+/**
+ * One entry in `playtime_audit.json`.
+ */
+export type PlaytimeAuditEntry = { id: string; game_id: string; 
+/**
+ * Unix seconds.
+ */
+at: number; reason: string; change: PlaytimeChange; 
+/**
+ * The entry that undid this one, if it was reverted.
+ */
+reverted_by?: string | null }
+/**
+ * A manual play time correction.
+ */
+export type PlaytimeChange = { type: "SessionAdded"; session: SessionRecord } | { type: "SessionEdited"; before: SessionRecord; after: SessionRecord } | { type: "SessionDeleted"; session: SessionRecord } | 
+/**
+ * A day's total in the daily stats was set by hand, in minutes.
+ */
+{ type: "DailyAdjusted"; date: string; before: number; after: number }
+/**
+ * CPU scheduling priority for launched games: a nice level on Unix, a
+ * priority class on Windows.
+ */
+export type ProcessPriority = "Idle" | "BelowNormal" | "Normal" | "AboveNormal" | "High"
+/**
+ * CPU and memory use of a game's process tree at one point in time.
+ */
+export type ResourceUsage = { 
+/**
+ * Summed over all processes, in percent of one core.
+ */
+cpu_percent: number; memory_bytes: number }
+/**
+ * How a game's executable is run. `path` is the Wine binary (or build
+ * folder) for `CustomWine` and the Proton install folder for `Proton`.
+ */
+export type RunnerConfig = { kind?: RunnerKind; path?: string | null; prefix?: string | null }
+export type RunnerKind = "Native" | "SystemWine" | "CustomWine" | "Proton"
+/**
+ * A running game as reported to the frontend.
+ */
+export type RunningSession = { game_id: string; profile_id: string | null; title: string; cover_url: string | null; started_at: number; elapsed_secs: number }
+/**
+ * Runs a game cut off from the network and the home directory (Linux only).
+ * The game's directory and Wine prefix are always reachable.
+ */
+export type SandboxConfig = { enabled?: boolean; tool?: SandboxTool; allow_network?: boolean; 
+/**
+ * Extra directories the game may write to, e.g. its save directory.
+ */
+allowed_paths?: string[] }
+export type SandboxTool = 
+/**
+ * Bubblewrap if installed, else firejail.
+ */
+"Auto" | "Bubblewrap" | "Firejail"
+export type ScanCandidate = { title: string; path: string; folder: string; alternatives: string[] }
+export type SessionKind = "Tracked" | 
+/**
+ * Play time recorded before the session log existed.
+ */
+"Legacy" | 
+/**
+ * Entered by hand to correct tracked play time.
+ */
+"Manual"
+/**
+ * One finished play session in `session_history.json`.
+ */
+export type SessionRecord = { id: string; game_id: string; profile_id?: string | null; kind?: SessionKind; started_at: number; ended_at: number; active_secs: number; excluded?: ExcludedSpan[]; exit_code?: number | null; 
+/**
+ * Signal that terminated the game, on Unix.
+ */
+exit_signal?: number | null; 
+/**
+ * The game exited with an error, was killed by a signal, or quit right
+ * after launch.
+ */
+crashed?: boolean; resources?: SessionResources | null; 
+/**
+ * Seconds east of UTC when the session started. Days are split in this
+ * timezone, so a later timezone change doesn't move past sessions.
+ */
+utc_offset?: number; 
+/**
+ * Minutes the session added to each `YYYY-MM-DD` day of the daily
+ * stats. Removing it takes back exactly these, even after
+ * `day_start_hour` changed. Empty for records from before this was kept.
+ */
+daily?: ([string, number])[] }
+/**
+ * Average and peak resource use over a session.
+ */
+export type SessionResources = { samples: number; avg_cpu_percent: number; peak_cpu_percent: number; avg_memory_bytes: number; peak_memory_bytes: number }
 export type VndbAuthInfo = { id: string; username: string }
 export type VndbCharacter = { id: string; name: string; original: string | null; aliases: string[] | null; image: VndbImage | null; description: string | null; blood_type: string | null; height: number | null; weight: number | null; bust: number | null; waist: number | null; hips: number | null; cup: string | null; age: number | null; birthday: number[] | null; sex: string[] | null; vns: VndbCharacterVn[] | null; traits: VndbTrait[] | null }
 export type VndbCharacterVn = { id: string; role: string; spoiler?: number }
@@ -216,6 +785,17 @@ export type VndbTag = { id: string; name: string; rating: number; spoiler?: numb
 export type VndbTrait = { id: string; name: string; group_id: string | null; group_name: string | null; spoiler?: number }
 export type VndbUserListItem = { id: string; vote: number | null; labels: VndbLabel[] | null; started: string | null; finished: string | null }
 export type VndbVnDetail = { id: string; title: string; image: VndbImage | null; released: string | null; rating: number | null; description: string | null; length: number | null; length_minutes: number | null; tags: VndbTag[] | null; developers: VndbProducer[] | null }
+export type WineArch = "Win32" | "Win64"
+export type WinePrefix = { id: string; name: string; path: string; arch?: WineArch; 
+/**
+ * Wine binary used to manage the prefix; `None` uses the system `wine`.
+ */
+wine_path?: string | null; locale?: string | null; dll_overrides?: Partial<{ [key in string]: string }>; created_at: string; 
+/**
+ * The folder was created by the launcher rather than adopted, so its
+ * files may be deleted along with the prefix.
+ */
+created_by_launcher?: boolean }
 
 /** tauri-specta globals **/
 
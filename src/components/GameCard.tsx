@@ -7,6 +7,7 @@ import {
   EyeOff,
   Eye,
   MoreHorizontal,
+  Square,
 } from "lucide-solid";
 import type { Game } from "../types";
 
@@ -16,6 +17,7 @@ interface GameCardProps {
   showHidden: boolean;
   formatPlayTime: (m: number) => string;
   onPlay: (id: string) => void;
+  onStop: (id: string) => void;
   onRemove: (id: string) => void;
   onSearch: (game: Game) => void;
   onClick: (game: Game) => void;
@@ -131,15 +133,31 @@ export function GameCard(props: GameCardProps) {
                 {props.formatPlayTime(props.game.play_time)}
               </span>
 
-              <button
-                onClick={(e) => {
-                  e.stopPropagation();
-                  props.onPlay(props.game.id);
-                }}
-                class="w-10 h-10 rounded-full bg-white text-black flex items-center justify-center hover:scale-110 active:scale-95 transition-all shadow-lg shadow-white/20"
+              <Show
+                when={props.isRunning}
+                fallback={
+                  <button
+                    onClick={(e) => {
+                      e.stopPropagation();
+                      props.onPlay(props.game.id);
+                    }}
+                    class="w-10 h-10 rounded-full bg-white text-black flex items-center justify-center hover:scale-110 active:scale-95 transition-all shadow-lg shadow-white/20"
+                  >
+                    <Play class="w-4 h-4 fill-current ml-0.5" />
+                  </button>
+                }
               >
-                <Play class="w-4 h-4 fill-current ml-0.5" />
-              </button>
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    props.onStop(props.game.id);
+                  }}
+                  class="w-10 h-10 rounded-full bg-red-500 text-white flex items-center justify-center hover:scale-110 active:scale-95 transition-all shadow-lg shadow-red-500/20"
+                  title="Stop Game"
+                >
+                  <Square class="w-4 h-4 fill-current" />
+                </button>
+              </Show>
             </div>
           </div>
         </div>
@@ -171,16 +189,32 @@ export function GameCard(props: GameCardProps) {
           }}
           onClick={(e) => e.stopPropagation()}
         >
-          <button
-            onClick={(e) => {
-              e.stopPropagation();
-              props.onPlay(props.game.id);
-              closeMenu();
-            }}
-            class="w-full flex items-center gap-2.5 px-4 py-2.5 text-sm text-slate-200 hover:bg-[#334155] hover:text-white transition-colors"
+          <Show
+            when={props.isRunning}
+            fallback={
+              <button
+                onClick={(e) => {
+                  e.stopPropagation();
+                  props.onPlay(props.game.id);
+                  closeMenu();
+                }}
+                class="w-full flex items-center gap-2.5 px-4 py-2.5 text-sm text-slate-200 hover:bg-[#334155] hover:text-white transition-colors"
+              >
+                <Play class="w-4 h-4 text-green-400" /> Play Game
+              </button>
+            }
           >
-            <Play class="w-4 h-4 text-green-400" /> Play Game
-          </button>
+            <button
+              onClick={(e) => {
+                e.stopPropagation();
+                props.onStop(props.game.id);
+                closeMenu();
+              }}
+              class="w-full flex items-center gap-2.5 px-4 py-2.5 text-sm text-slate-200 hover:bg-[#334155] hover:text-white transition-colors"
+            >
+              <Square class="w-4 h-4 text-red-400" /> Stop Game
+            </button>
+          </Show>
           <button
             onClick={(e) => {
               e.stopPropagation();
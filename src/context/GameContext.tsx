@@ -1,27 +1,29 @@
 import { createContext, createSignal, useContext, onCleanup, ParentComponent } from "solid-js";
 import { listen } from "@tauri-apps/api/event";
-import type { GameMetadata } from "../bindings";
+import type { AppError, GameMetadata } from "../bindings";
 import type { GameExitedPayload } from "../types";
 import * as api from "../api";
 
 interface GameContextValue {
     games: () => GameMetadata[];
-    runningGame: () => string | null;
+    runningGames: () => string[];
     loading: () => boolean;
     loadGames: () => Promise<void>;
     addGame: (path: string) => Promise<GameMetadata | null>;
     removeGame: (id: string) => Promise<void>;
     hideGame: (id: string, hidden: boolean) => Promise<void>;
     updateGame: (game: GameMetadata) => Promise<void>;
-    launchGame: (id: string) => Promise<void>;
-    stopTracking: () => Promise<number>;
+    /** Resolves to the error when the game could not be launched. */
+    launchGame: (id: string) => Promise<AppError | null>;
+    stopTracking: (id: string) => Promise<number>;
+    forceStop: (id: string) => Promise<void>;
 }
 
 const GameContext = createContext<GameContextValue>();
 
 export const GameProvider: ParentComponent = (props) => {
     const [games, setGames] = createSignal<GameMetadata[]>([]);
-    const [runningGame, setRunningGame] = createSignal<string | null>(null);
+    const [runningGames, setRunningGames] = createSignal<string[]>([]);
     const [loading, setLoading] = createSignal(true);
 
     const loadGames = async () => {
@@ -29,6 +31,8 @@ export const GameProvider: ParentComponent = (props) => {
         try {
             const games = await api.getAllGames();
             setGames(games);
+            const sessions = await api.pollRunningGame();
+            setRunningGames(sessions.map((s) => s.game_id));
         } catch (e) {
             console.error("Failed to load games:", e);
         }
@@ -65,24 +69,33 @@ export const GameProvider: ParentComponent = (props) => {
         }
     };
 
-    const launchGame = async (id: string) => {
+    const launchGame = async (id: string): Promise<AppError | null> => {
         const result = await api.launchGame(id);
-        if (result.status === "ok") {
-            setRunningGame(id);
+        if (result.status === "error") {
+            return result.error;
         }
+        setRunningGames((prev) => (prev.includes(id) ? prev : [...prev, id]));
+        return null;
     };
 
-    const stopTracking = async (): Promise<number> => {
-        const result = await api.stopTracking();
+    const stopTracking = async (id: string): Promise<number> => {
+        const result = await api.stopTracking(id);
         if (result.status === "ok") {
-            setRunningGame(null);
+            setRunningGames((prev) => prev.filter((g) => g !== id));
             return result.data;
         }
         return 0;
     };
 
-    listen<GameExitedPayload>("game-exited", (event) => {
-        setRunningGame(null);
+    const forceStop = async (id: string) => {
+        const result = await api.forceStopGame(id);
+        if (result.status === "error") {
+            console.error("Failed to stop game:", result.error);
+        }
+    };
+
+    const unlisten = listen<GameExitedPayload>("game-exited", (event) => {
+        setRunningGames((prev) => prev.filter((g) => g !== event.payload.game_id));
         setGames((prev) =>
             prev.map((g) =>
                 g.id === event.payload.game_id
@@ -90,15 +103,14 @@ export const GameProvider: ParentComponent = (props) => {
                     : g
             )
         );
-    }).then((unlisten) => {
-        onCleanup(unlisten);
     });
+    onCleanup(() => unlisten.then((f) => f()));
 
     loadGames();
 
     const value: GameContextValue = {
         games,
-        runningGame,
+        runningGames,
         loading,
         loadGames,
         addGame,
@@ -107,6 +119,7 @@ export const GameProvider: ParentComponent = (props) => {
         updateGame,
         launchGame,
         stopTracking,
+        forceStop,
     };
 
     return <GameContext.Provider value={value}>{props.children}</GameContext.Provider>;
@@ -0,0 +1,59 @@
+import { createSignal, onCleanup, onMount } from "solid-js";
+import { listen } from "@tauri-apps/api/event";
+import { ask } from "@tauri-apps/plugin-dialog";
+import { useNavigate } from "@solidjs/router";
+import { useGame } from "../context";
+import * as api from "../api";
+
+/** Acts on `--launch` / `--game` arguments and `alka://` links. */
+export function useLaunchRequests() {
+    const game = useGame();
+    const navigate = useNavigate();
+    const [errorMessage, setErrorMessage] = createSignal<string | null>(null);
+
+    let errorTimeout: ReturnType<typeof setTimeout> | undefined;
+    const showError = (message: string) => {
+        clearTimeout(errorTimeout);
+        setErrorMessage(message);
+        errorTimeout = setTimeout(() => setErrorMessage(null), 4000);
+    };
+    onCleanup(() => clearTimeout(errorTimeout));
+
+    const handleRequests = async () => {
+        const requests = await api.takeLaunchRequests();
+        for (const request of requests) {
+            const result = await api.resolveLaunchRequest(request);
+            if (result.status === "error") {
+                showError(`Couldn't open link: ${result.error.message}`);
+                continue;
+            }
+            const target = result.data;
+            if (request.type === "Launch") {
+                // Any web page can open an alka:// link.
+                const confirmed =
+                    !request.from_link ||
+                    (await ask(`Launch ${target.title}?`, {
+                        title: "Open link",
+                        kind: "info",
+                        okLabel: "Launch",
+                        cancelLabel: "Cancel",
+                    }));
+                const error = confirmed ? await game.launchGame(target.id) : null;
+                if (error) {
+                    showError(`Couldn't launch ${target.title}: ${error.message}`);
+                }
+            } else if (target.vndb_id) {
+                navigate(`/game/${target.id}`);
+            } else {
+                showError(`${target.title} is not linked to VNDB`);
+            }
+        }
+    };
+
+    const unlisten = listen("launch-requests", handleRequests);
+    onCleanup(() => unlisten.then((f) => f()));
+
+    onMount(handleRequests);
+
+    return { errorMessage, dismissError: () => setErrorMessage(null) };
+}
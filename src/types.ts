@@ -16,9 +16,50 @@ export type {
     VndbAuthInfo,
     AppSettings,
     DailyPlaytimeData,
+    LaunchOptions,
+    LaunchProfile,
+    RunnerConfig,
+    RunnerKind,
+    WineArch,
+    WinePrefix,
+    ScanCandidate,
+    HookCommands,
+    RunningSession,
+    IdleSourceKind,
+    SessionRecord,
+    SessionKind,
+    ExcludedSpan,
+    ExclusionReason,
+    PlaytimeAuditEntry,
+    PlaytimeChange,
+    ProcessPriority,
+    LaunchPreview,
+    SandboxConfig,
+    SandboxTool,
+    ResourceUsage,
+    SessionResources,
+    LaunchTarget,
+    EmulatorConfig,
+    LaunchRequest,
 } from "./bindings";
 
 export interface GameExitedPayload {
     game_id: string;
+    profile_id: string | null;
     play_minutes: number;
+    excluded_minutes: number;
+    active_secs: number;
+    exit_code: number | null;
+    exit_signal: number | null;
+    crashed: boolean;
+    force_stopped: boolean;
+    log_path: string | null;
+}
+
+export interface ScanProgressPayload {
+    root: string;
+    current_dir: string;
+    scanned_dirs: number;
+    found: number;
+    finished: boolean;
 }
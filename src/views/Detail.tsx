@@ -1,5 +1,5 @@
 import { Show } from "solid-js";
-import { Play, Clock, Loader2 } from "lucide-solid";
+import { Play, Square, Loader2 } from "lucide-solid";
 import { Sidebar } from "../components/Sidebar";
 import { GameInfoTab } from "../components/detail/GameInfoTab";
 import { CharacterList } from "../components/detail/CharacterList";
@@ -12,7 +12,7 @@ interface DetailProps {
     vnDetail: VndbVnDetail;
     characters: VndbCharacter[];
     userVn: VndbUserListItem | null;
-    runningGame: string | null;
+    runningGames: string[];
     settings: AppSettings;
     showSpoilers: boolean;
     setShowSpoilers: (show: boolean) => void;
@@ -21,6 +21,7 @@ interface DetailProps {
     onRefresh: () => void;
     onSettings: () => void;
     onLaunchGame: (id: string) => void;
+    onStopTracking: (id: string) => void;
     onSetStatus: (labelId: number) => void;
     onSetVote: (vote: number) => void;
     formatPlayTime: (m: number) => string;
@@ -80,9 +81,9 @@ export function Detail(props: DetailProps) {
 
                     {/* Primary Action */}
                     <Show when={props.game}>
-                        <Show when={props.runningGame !== props.game.id} fallback={
-                            <button class="px-6 py-2.5 bg-green-500/10 text-green-400 border border-green-500/20 rounded-full font-bold text-sm tracking-wide flex items-center gap-2 cursor-default">
-                                <Clock class="w-4 h-4" /> RUNNING
+                        <Show when={!props.runningGames.includes(props.game.id)} fallback={
+                            <button onClick={() => props.onStopTracking(props.game.id)} class="px-6 py-2.5 bg-red-500/10 text-red-400 border border-red-500/20 hover:bg-red-500/20 rounded-full font-bold text-sm tracking-wide flex items-center gap-2 transition-all">
+                                <Square class="w-4 h-4 fill-current" /> STOP
                             </button>
                         }>
                             <button onClick={() => props.onLaunchGame(props.game.id)} class="group relative px-8 py-2.5 bg-white text-black rounded-full font-bold text-sm tracking-wide overflow-hidden shadow-[0_0_20px_rgba(255,255,255,0.3)] hover:shadow-[0_0_30px_rgba(255,255,255,0.5)] transition-all">
@@ -2,7 +2,6 @@ import { Show, For, createSignal, createEffect, onCleanup } from "solid-js";
 import {
   Plus,
   Gamepad2,
-  Clock,
   Settings,
   Search,
   LayoutGrid,
@@ -20,7 +19,7 @@ import type { SortBy, SortOrder } from "../hooks/useLibraryFilters";
 interface LibraryProps {
   games: Game[];
   filteredGames: Game[];
-  runningGame: string | null;
+  runningGames: string[];
   loading: boolean;
   authUser: string | null;
   // Filter props
@@ -35,7 +34,7 @@ interface LibraryProps {
   // Action props
   formatPlayTime: (m: number) => string;
   onAddGame: () => void;
-  onStopTracking: () => void;
+  onStopTracking: (id: string) => void;
   onLaunchGame: (id: string) => void;
   onRemoveGame: (id: string) => void;
   onSearchGame: (game: Game) => void;
@@ -138,16 +137,6 @@ export function Library(props: LibraryProps) {
             </Show>
 
             <div class="flex items-center gap-3 pl-4 border-l border-white/10">
-              <Show when={props.runningGame}>
-                <button
-                  onClick={props.onStopTracking}
-                  class="flex items-center gap-2 px-4 py-2 bg-red-500/10 text-red-400 border border-red-500/20 hover:bg-red-500/20 rounded-xl transition-all"
-                >
-                  <Clock class="w-4 h-4 animate-pulse" />
-                  <span class="text-sm font-bold">Stop Game</span>
-                </button>
-              </Show>
-
               <button
                 onClick={props.onAddGame}
                 class="p-2.5 text-slate-400 hover:text-white hover:bg-slate-700/50 rounded-xl transition-all relative group"
@@ -311,7 +300,7 @@ export function Library(props: LibraryProps) {
                     {(game) => (
                       <div
                         class={`group flex items-center gap-4 p-3 bg-[#1E293B] rounded-xl border border-transparent hover:border-blue-500/50 cursor-pointer transition-all ${game.is_hidden && props.showHidden ? "opacity-50" : ""
-                          } ${props.runningGame === game.id ? "ring-2 ring-green-500" : ""}`}
+                          } ${props.runningGames.includes(game.id) ? "ring-2 ring-green-500" : ""}`}
                         onClick={() =>
                           game.vndb_id
                             ? props.onOpenDetail(game)
@@ -352,20 +341,33 @@ export function Library(props: LibraryProps) {
                         </div>
 
                         <div class="flex items-center gap-2 opacity-0 group-hover:opacity-100 transition-opacity">
-                          <Show when={props.runningGame === game.id}>
+                          <Show
+                            when={props.runningGames.includes(game.id)}
+                            fallback={
+                              <button
+                                onClick={(e) => {
+                                  e.stopPropagation();
+                                  props.onLaunchGame(game.id);
+                                }}
+                                class="px-4 py-2 bg-white text-black rounded-lg font-medium hover:bg-slate-200 transition-colors"
+                              >
+                                Play
+                              </button>
+                            }
+                          >
                             <span class="px-2 py-1 bg-green-500 text-black text-xs font-bold rounded animate-pulse">
                               RUNNING
                             </span>
+                            <button
+                              onClick={(e) => {
+                                e.stopPropagation();
+                                props.onStopTracking(game.id);
+                              }}
+                              class="px-4 py-2 bg-red-500/10 text-red-400 border border-red-500/20 rounded-lg font-medium hover:bg-red-500/20 transition-colors"
+                            >
+                              Stop
+                            </button>
                           </Show>
-                          <button
-                            onClick={(e) => {
-                              e.stopPropagation();
-                              props.onLaunchGame(game.id);
-                            }}
-                            class="px-4 py-2 bg-white text-black rounded-lg font-medium hover:bg-slate-200 transition-colors"
-                          >
-                            Play
-                          </button>
                         </div>
                       </div>
                     )}
@@ -379,10 +381,11 @@ export function Library(props: LibraryProps) {
                   {(game) => (
                     <GameCard
                       game={game}
-                      isRunning={props.runningGame === game.id}
+                      isRunning={props.runningGames.includes(game.id)}
                       showHidden={props.showHidden}
                       formatPlayTime={props.formatPlayTime}
                       onPlay={props.onLaunchGame}
+                      onStop={props.onStopTracking}
                       onRemove={props.onRemoveGame}
                       onSearch={props.onSearchGame}
                       onClick={(g) =>